@@ -1,3 +1,5 @@
 #!/usr/bin/env bash
 set -euo pipefail
-scrubby --clipboard
+# Config files and SCRUBBY_* variables are picked up as usual; extra
+# arguments such as --profile are passed through.
+exec scrubby scrub "$@"
@@ -0,0 +1,169 @@
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use crate::rules::regex_error;
+
+/// Known-safe values that are detected but left in place.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Allowlist {
+    entries: Vec<AllowEntry>,
+}
+
+#[derive(Debug, Clone)]
+pub enum AllowEntry {
+    /// Exact value, e.g. a public support address.
+    Value(String),
+    /// Regex that must match the whole value.
+    Pattern(Regex),
+    /// Email domain; also covers its subdomains.
+    Domain(String),
+    /// SHA-256 of the value, so the allowlist itself doesn't leak it.
+    Sha256([u8; 32]),
+}
+
+impl PartialEq for AllowEntry {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AllowEntry::Value(a), AllowEntry::Value(b)) => a == b,
+            (AllowEntry::Pattern(a), AllowEntry::Pattern(b)) => a.as_str() == b.as_str(),
+            (AllowEntry::Domain(a), AllowEntry::Domain(b)) => a == b,
+            (AllowEntry::Sha256(a), AllowEntry::Sha256(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl AllowEntry {
+    /// Builds an entry from a config key (`allow`, `allow_regex`,
+    /// `allow_domain`, `allow_sha256`). Returns `Ok(None)` for other keys.
+    pub fn from_config(key: &str, value: &str) -> Result<Option<AllowEntry>, String> {
+        let entry = match key {
+            "allow" => AllowEntry::Value(value.to_string()),
+            "allow_regex" => {
+                let re = Regex::new(&format!("^(?:{})$", value)).map_err(|e| {
+                    format!("Invalid allow_regex '{}' ({})", value, regex_error(&e))
+                })?;
+                AllowEntry::Pattern(re)
+            }
+            "allow_domain" => {
+                let domain = value.trim_start_matches('@').to_ascii_lowercase();
+                AllowEntry::Domain(domain)
+            }
+            "allow_sha256" => {
+                let bytes = hex::decode(value)
+                    .ok()
+                    .and_then(|b| <[u8; 32]>::try_from(b).ok())
+                    .ok_or_else(|| {
+                        format!("Invalid allow_sha256 '{}' (expected 64 hex chars)", value)
+                    })?;
+                AllowEntry::Sha256(bytes)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(entry))
+    }
+
+    /// An `allow_sha256` entry for `value`.
+    pub fn hashed(value: &str) -> AllowEntry {
+        AllowEntry::Sha256(Sha256::digest(value.as_bytes()).into())
+    }
+
+    /// The config key and value this entry was read from.
+    pub fn to_config(&self) -> (&'static str, String) {
+        match self {
+            AllowEntry::Value(v) => ("allow", v.clone()),
+            AllowEntry::Pattern(re) => {
+                let inner = &re.as_str()["^(?:".len()..re.as_str().len() - ")$".len()];
+                ("allow_regex", inner.to_string())
+            }
+            AllowEntry::Domain(d) => ("allow_domain", d.clone()),
+            AllowEntry::Sha256(h) => ("allow_sha256", hex::encode(h)),
+        }
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        match self {
+            AllowEntry::Value(v) => v == value,
+            AllowEntry::Pattern(re) => re.is_match(value),
+            AllowEntry::Domain(d) => match value.rsplit_once('@') {
+                Some((_, host)) => {
+                    let host = host.to_ascii_lowercase();
+                    host == *d || host.ends_with(&format!(".{}", d))
+                }
+                None => false,
+            },
+            AllowEntry::Sha256(h) => Sha256::digest(value.as_bytes()).as_slice() == h,
+        }
+    }
+}
+
+impl Allowlist {
+    pub fn push(&mut self, entry: AllowEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn extend(&mut self, other: Allowlist) {
+        self.entries.extend(other.entries);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[AllowEntry] {
+        &self.entries
+    }
+
+    pub fn allows(&self, value: &str) -> bool {
+        self.entries.iter().any(|e| e.matches(value))
+    }
+}
+
+/// Hex SHA-256 of `value`, for use in `allow_sha256` entries.
+pub fn hash_value(value: &str) -> String {
+    hex::encode(Sha256::digest(value.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(entries: &[(&str, &str)]) -> Allowlist {
+        let mut a = Allowlist::default();
+        for (k, v) in entries {
+            a.push(AllowEntry::from_config(k, v).unwrap().unwrap());
+        }
+        a
+    }
+
+    #[test]
+    fn matches_each_entry_kind() {
+        let a = list(&[
+            ("allow", "support@acme.io"),
+            ("allow_regex", r"10\.0\.0\.\d+"),
+            ("allow_domain", "example.com"),
+            (
+                "allow_sha256",
+                &hash_value("123e4567-e89b-42d3-a456-556642440000"),
+            ),
+        ]);
+        assert!(a.allows("support@acme.io"));
+        assert!(a.allows("10.0.0.7"));
+        assert!(!a.allows("110.0.0.7"));
+        assert!(a.allows("bob@mail.Example.com"));
+        assert!(!a.allows("bob@notexample.com"));
+        assert!(a.allows("123e4567-e89b-42d3-a456-556642440000"));
+        assert!(!a.allows("sales@acme.io"));
+        assert_eq!(
+            a.entries()[1].to_config(),
+            ("allow_regex", r"10\.0\.0\.\d+".to_string())
+        );
+    }
+
+    #[test]
+    fn rejects_bad_entries() {
+        assert!(AllowEntry::from_config("allow_regex", "(").is_err());
+        assert!(AllowEntry::from_config("allow_sha256", "abc").is_err());
+        assert_eq!(AllowEntry::from_config("json_report", "true"), Ok(None));
+    }
+}
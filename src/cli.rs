@@ -0,0 +1,450 @@
+//! Command-line interface of the `scrubby` binary.
+
+use std::path::PathBuf;
+
+use clap::{Args, ColorChoice, Parser, Subcommand};
+use scrubby::preview::DiffStyle;
+use scrubby::severity::Severity;
+use scrubby::walk::{parse_size, DEFAULT_MAX_FILE_SIZE};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "scrubby",
+    version,
+    about = "Sanitize clipboard text before pasting it into AI tools",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Flags of the flag-only CLI, kept so `scrubby --clipboard` and friends
+    /// keep working.
+    #[command(flatten)]
+    pub legacy: LegacyArgs,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Sanitize the clipboard, stdin or a file once
+    Scrub(ScrubArgs),
+    /// Watch the clipboard and sanitize it on every change (experimental)
+    Watch(WatchArgs),
+    /// Report findings in files or stdin without changing anything
+    Check(CheckArgs),
+    /// Show why each value in the clipboard, stdin or files was or was not
+    /// redacted
+    Explain(ExplainArgs),
+    /// Install or remove the git pre-commit hook
+    #[command(subcommand)]
+    Hook(HookCommand),
+    /// Create, check and inspect config files
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Inspect the Pro license
+    #[command(subcommand)]
+    License(LicenseCommand),
+}
+
+/// Settings shared by every command that scrubs text.
+#[derive(Debug, Clone, Default, Args)]
+pub struct ScrubSettings {
+    /// Print a JSON report instead of the text summary
+    #[arg(long)]
+    pub json: bool,
+    /// Number placeholders per kind, e.g. <EMAIL_1>
+    #[arg(long)]
+    pub stable: bool,
+    /// Rejoin secrets split across line wraps or string concatenation
+    #[arg(long)]
+    pub reassemble: bool,
+    /// Always redact the terms listed in this file (repeatable)
+    #[arg(long, value_name = "PATH")]
+    pub denylist: Vec<PathBuf>,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+/// Selects the config on top of the discovered files.
+#[derive(Debug, Clone, Default, Args)]
+pub struct ConfigArgs {
+    /// Load this config file on top of the discovered ones
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Use a named config profile [env: SCRUBBY_PROFILE]
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Args)]
+pub struct ScrubArgs {
+    /// Read from stdin and print the sanitized text
+    #[arg(long, conflicts_with = "file")]
+    pub stdin: bool,
+    /// Read files, or every file under directories, and print the sanitized
+    /// text (repeatable)
+    #[arg(long, value_name = "PATH", num_args = 1..)]
+    pub file: Vec<PathBuf>,
+    #[command(flatten)]
+    pub walk: WalkArgs,
+    /// Rewrite the files instead of printing them; unchanged files are left alone
+    #[arg(long, requires = "file", conflicts_with = "output_dir")]
+    pub in_place: bool,
+    /// With --in-place, first copy each changed file to <file><SUFFIX>
+    #[arg(
+        long,
+        value_name = "SUFFIX",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = ".bak",
+        requires = "in_place"
+    )]
+    pub backup: Option<String>,
+    /// Write sanitized copies into this directory, mirroring the inputs
+    #[arg(long, value_name = "DIR", requires = "file")]
+    pub output_dir: Option<PathBuf>,
+    /// Ask about each finding before redacting it
+    #[arg(long, conflicts_with = "dry_run")]
+    pub review: bool,
+    /// Add values allowlisted during --review to the user-wide allowlist
+    #[arg(long, requires = "review")]
+    pub save_allowlist: bool,
+    /// Print a diff of what would be redacted instead of writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Layout of the --dry-run diff: unified or inline
+    #[arg(
+        long,
+        value_name = "STYLE",
+        value_parser = parse_diff_style,
+        default_value = "unified",
+        requires = "dry_run"
+    )]
+    pub diff: DiffStyle,
+    /// Show only the first two characters of each original value in the diff
+    #[arg(long, requires = "dry_run")]
+    pub mask: bool,
+    /// Color the diff: auto (when stdout is a terminal), always or never
+    #[arg(long, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorChoice,
+    /// Stream a JSON line per finding and a final summary to stdout or
+    /// stderr (the default) instead of the text summary
+    #[arg(
+        long,
+        value_name = "STREAM",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "stderr",
+        value_parser = parse_stream,
+        conflicts_with = "json"
+    )]
+    pub ndjson: Option<Stream>,
+    /// Exit with status 5 if a finding is at least this severe
+    #[arg(long, value_name = "SEVERITY", value_parser = parse_severity)]
+    pub fail_on: Option<Severity>,
+    #[command(flatten)]
+    pub settings: ScrubSettings,
+}
+
+/// Where `--ndjson` records go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    pub const NAMES: [&'static str; 2] = ["stdout", "stderr"];
+
+    pub fn from_name(name: &str) -> Option<Stream> {
+        match name {
+            "stdout" => Some(Stream::Stdout),
+            "stderr" => Some(Stream::Stderr),
+            _ => None,
+        }
+    }
+}
+
+/// Limits for files found in directories.
+#[derive(Debug, Clone, Args)]
+pub struct WalkArgs {
+    /// Skip files bigger than this, e.g. 500K or 10M
+    #[arg(long, value_name = "SIZE", value_parser = parse_file_size, default_value = "1M")]
+    pub max_file_size: u64,
+}
+
+impl Default for WalkArgs {
+    fn default() -> Self {
+        WalkArgs {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ExplainArgs {
+    /// Read from stdin instead of the clipboard
+    #[arg(long, conflicts_with = "file")]
+    pub stdin: bool,
+    /// Read files, or every file under directories (repeatable)
+    #[arg(long, value_name = "PATH", num_args = 1..)]
+    pub file: Vec<PathBuf>,
+    #[command(flatten)]
+    pub walk: WalkArgs,
+    #[command(flatten)]
+    pub settings: ScrubSettings,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct WatchArgs {
+    /// Clipboard poll interval in milliseconds [default: 750, or interval_ms from config]
+    #[arg(long, value_name = "MS", value_parser = parse_interval)]
+    pub interval_ms: Option<u64>,
+    #[command(flatten)]
+    pub settings: ScrubSettings,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct CheckArgs {
+    /// Files to check; none or `-` reads stdin
+    #[arg(value_name = "PATH")]
+    pub paths: Vec<PathBuf>,
+    /// Check only the lines added in `git diff --cached`
+    #[arg(long, conflicts_with = "paths")]
+    pub staged: bool,
+    #[command(flatten)]
+    pub walk: WalkArgs,
+    /// Only count findings at least this severe
+    #[arg(long, value_name = "SEVERITY", value_parser = parse_severity, default_value = "low")]
+    pub fail_on: Severity,
+    /// Number of counted findings allowed before the check fails
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub max_findings: usize,
+    /// Rejoin secrets split across line wraps or string concatenation
+    #[arg(long)]
+    pub reassemble: bool,
+    /// Always flag the terms listed in this file (repeatable)
+    #[arg(long, value_name = "PATH")]
+    pub denylist: Vec<PathBuf>,
+    /// How to print findings: text (one line each), sarif or ndjson
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_parser = parse_check_format,
+        default_value = "text"
+    )]
+    pub format: CheckFormat,
+    #[command(flatten)]
+    pub config: ConfigArgs,
+}
+
+/// Output of `scrubby check`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CheckFormat {
+    /// `path:line:column: severity kind`, one finding per line.
+    #[default]
+    Text,
+    /// A SARIF 2.1.0 log, for code-scanning tools.
+    Sarif,
+    /// A JSON line per finding as it is found, then a summary line.
+    Ndjson,
+}
+
+impl CheckFormat {
+    pub const NAMES: [&'static str; 3] = ["text", "sarif", "ndjson"];
+
+    pub fn from_name(name: &str) -> Option<CheckFormat> {
+        match name {
+            "text" => Some(CheckFormat::Text),
+            "sarif" => Some(CheckFormat::Sarif),
+            "ndjson" => Some(CheckFormat::Ndjson),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum HookCommand {
+    /// Install a pre-commit hook running `scrubby check --staged`
+    Install {
+        /// Replace an existing pre-commit hook not written by scrubby
+        #[arg(long)]
+        force: bool,
+        /// Passed on to `scrubby check`
+        #[arg(long, value_name = "SEVERITY", value_parser = parse_severity)]
+        fail_on: Option<Severity>,
+        /// Passed on to `scrubby check`
+        #[arg(long, value_name = "N")]
+        max_findings: Option<usize>,
+    },
+    /// Remove the pre-commit hook installed by scrubby
+    Uninstall,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Write a commented starter config listing every setting
+    Init {
+        /// Where to write it; `-` prints it instead
+        #[arg(default_value = ".scrubby.toml")]
+        path: PathBuf,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check a config file and report every error in it, by line
+    Validate { path: PathBuf },
+    /// Print the effective config and where each value came from
+    Show(ConfigArgs),
+    /// Print an allow_sha256 entry for a known-safe value
+    HashValue { value: String },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum LicenseCommand {
+    /// Check the installed license and show who it is for
+    Status,
+    /// Print this machine's id, for device-bound licenses
+    DeviceId,
+}
+
+/// `scrubby [--clipboard | --watch] [options]`, the flag-only CLI.
+#[derive(Debug, Clone, Default, Args)]
+pub struct LegacyArgs {
+    #[arg(long, hide = true, conflicts_with = "watch")]
+    pub clipboard: bool,
+    #[arg(long, hide = true, conflicts_with_all = ["stdin", "file", "fail_on", "max_file_size", "in_place", "output_dir", "dry_run", "review", "ndjson"])]
+    pub watch: bool,
+    #[arg(long, hide = true, value_name = "MS", value_parser = parse_interval)]
+    pub interval_ms: Option<u64>,
+    #[arg(long, hide = true)]
+    pub print_config: bool,
+    #[arg(long, hide = true)]
+    pub device_id: bool,
+    #[arg(long, hide = true, value_name = "VALUE")]
+    pub hash_value: Option<String>,
+    #[command(flatten)]
+    pub scrub: ScrubArgs,
+}
+
+impl LegacyArgs {
+    /// The subcommand these flags stand for.
+    pub fn into_command(self) -> Command {
+        if let Some(value) = self.hash_value {
+            Command::Config(ConfigCommand::HashValue { value })
+        } else if self.device_id {
+            Command::License(LicenseCommand::DeviceId)
+        } else if self.print_config {
+            Command::Config(ConfigCommand::Show(self.scrub.settings.config))
+        } else if self.watch {
+            Command::Watch(WatchArgs {
+                interval_ms: self.interval_ms,
+                settings: self.scrub.settings,
+            })
+        } else {
+            Command::Scrub(self.scrub)
+        }
+    }
+}
+
+fn parse_severity(value: &str) -> Result<Severity, String> {
+    Severity::from_name(value)
+        .ok_or_else(|| format!("expected one of: {}", Severity::NAMES.join(", ")))
+}
+
+fn parse_diff_style(value: &str) -> Result<DiffStyle, String> {
+    DiffStyle::from_name(value)
+        .ok_or_else(|| format!("expected one of: {}", DiffStyle::NAMES.join(", ")))
+}
+
+fn parse_check_format(value: &str) -> Result<CheckFormat, String> {
+    CheckFormat::from_name(value)
+        .ok_or_else(|| format!("expected one of: {}", CheckFormat::NAMES.join(", ")))
+}
+
+fn parse_stream(value: &str) -> Result<Stream, String> {
+    Stream::from_name(value).ok_or_else(|| format!("expected one of: {}", Stream::NAMES.join(", ")))
+}
+
+fn parse_file_size(value: &str) -> Result<u64, String> {
+    parse_size(value).ok_or_else(|| "expected a size such as 500000, 64K or 10M".to_string())
+}
+
+fn parse_interval(value: &str) -> Result<u64, String> {
+    match value.parse::<u64>() {
+        Ok(n) if n >= 100 => Ok(n),
+        _ => Err("must be a number of milliseconds, at least 100".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli_definition_is_valid() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn legacy_flags_map_to_subcommands() {
+        let cli = Cli::try_parse_from(["scrubby", "--watch", "--interval-ms", "200"]).unwrap();
+        match cli.legacy.into_command() {
+            Command::Watch(w) => assert_eq!(w.interval_ms, Some(200)),
+            other => panic!("unexpected {:?}", other),
+        }
+        let cli = Cli::try_parse_from(["scrubby", "--stdin", "--fail-on", "high"]).unwrap();
+        match cli.legacy.into_command() {
+            Command::Scrub(s) => {
+                assert!(s.stdin);
+                assert_eq!(s.fail_on, Some(Severity::High));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["scrubby", "--watch", "--stdin"]).is_err());
+        assert!(Cli::try_parse_from(["scrubby", "scrub", "--fail-on", "severe"]).is_err());
+    }
+
+    #[test]
+    fn backup_needs_in_place_and_defaults_to_bak() {
+        let cli =
+            Cli::try_parse_from(["scrubby", "scrub", "--file", "a", "--in-place", "--backup"])
+                .unwrap();
+        match cli.command {
+            Some(Command::Scrub(s)) => assert_eq!(s.backup.as_deref(), Some(".bak")),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["scrubby", "scrub", "--file", "a", "--backup"]).is_err());
+        assert!(Cli::try_parse_from(["scrubby", "scrub", "--in-place"]).is_err());
+    }
+
+    #[test]
+    fn ndjson_defaults_to_stderr() {
+        let cli = Cli::try_parse_from(["scrubby", "scrub", "--stdin", "--ndjson"]).unwrap();
+        match cli.command {
+            Some(Command::Scrub(s)) => assert_eq!(s.ndjson, Some(Stream::Stderr)),
+            other => panic!("unexpected {:?}", other),
+        }
+        let cli = Cli::try_parse_from(["scrubby", "scrub", "--ndjson=stdout"]).unwrap();
+        match cli.command {
+            Some(Command::Scrub(s)) => assert_eq!(s.ndjson, Some(Stream::Stdout)),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(Cli::try_parse_from(["scrubby", "scrub", "--ndjson", "--json"]).is_err());
+    }
+
+    #[test]
+    fn check_defaults_to_failing_on_any_finding() {
+        let cli = Cli::try_parse_from(["scrubby", "check", "a.log", "b.log"]).unwrap();
+        match cli.command {
+            Some(Command::Check(c)) => {
+                assert_eq!(c.paths.len(), 2);
+                assert_eq!(c.fail_on, Severity::Low);
+                assert_eq!(c.max_findings, 0);
+                assert_eq!(c.format, CheckFormat::Text);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}
@@ -1,16 +1,333 @@
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Default, Clone, PartialEq, Eq)]
+use crate::allowlist::{AllowEntry, Allowlist};
+use crate::denylist::Denylist;
+use crate::detectors::IpClasses;
+use crate::policy::Action;
+use crate::redactor::KINDS;
+use crate::rules::{self, Rule, RuleDraft};
+use crate::scoring::{Alphabet, DEFAULT_TOKEN_THRESHOLD};
+use crate::severity::{default_severity, Severity};
+use crate::ScrubOptions;
+
+mod discovery;
+mod starter;
+mod toml_format;
+
+pub use discovery::{discover, effective_entries, select_profile, Layer, Source, PROFILE_VAR};
+pub use starter::starter_config;
+
+/// Poll interval for watch mode when neither config nor CLI sets one.
+pub const DEFAULT_INTERVAL_MS: u64 = 750;
+
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Config {
     pub stable_placeholders: Option<bool>,
+    pub reassemble: Option<bool>,
+    pub token_threshold: Option<f64>,
+    /// Per-alphabet entropy floors from `entropy_<alphabet>` keys.
+    pub entropy: Vec<(Alphabet, f64)>,
+    /// Per-detector switches from `detectors.<name>.<param>` keys.
+    pub detectors: DetectorConfig,
+    /// Denylist file; relative paths are resolved against the config file.
+    pub denylist: Option<PathBuf>,
+    /// Custom detectors from `rule.<name>.<attr>` keys.
+    pub rules: Vec<Rule>,
+    /// Entries from `allow`, `allow_regex`, `allow_domain` and `allow_sha256`.
+    pub allowlist: Allowlist,
     pub json_report: Option<bool>,
     pub interval_ms: Option<u64>,
+    /// Actions from `policy.<kind>` and `policy.rule.<name>` keys.
+    pub policy: BTreeMap<String, Action>,
+    /// `policy.on_block`: clear the clipboard (`clear`) or leave it (`keep`).
+    pub clear_on_block: Option<bool>,
+    /// Per-kind severities from `severity.<kind>` keys.
+    pub severity: BTreeMap<String, Severity>,
+    /// Named profiles from `profile.<name>.<key>` keys or `[profiles.<name>]`.
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Settings selected with `--profile`, applied on top of the rest of the
+/// config after the profile it extends.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Profile {
+    pub extends: Option<String>,
+    pub config: Config,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DetectorConfig {
+    pub email: Option<bool>,
+    pub ip: Option<bool>,
+    pub uuid: Option<bool>,
+    pub jwt: Option<bool>,
+    pub token: Option<bool>,
+    pub token_min_length: Option<usize>,
+    pub ip_classes: Option<IpClasses>,
+}
+
+impl Config {
+    /// Applies the scrubbing settings of this config on top of `options`.
+    /// CLI-only settings (`json_report`, `interval_ms`, `denylist`) are left
+    /// to the caller.
+    pub fn apply(&self, options: &mut ScrubOptions) {
+        if let Some(v) = self.stable_placeholders {
+            options.stable_placeholders = v;
+        }
+        if let Some(v) = self.reassemble {
+            options.reassemble = v;
+        }
+        if let Some(v) = self.token_threshold {
+            options.token_threshold = v;
+        }
+        for &(alphabet, bits) in self.entropy.iter() {
+            options.entropy.set(alphabet, bits);
+        }
+        let d = &self.detectors;
+        let enabled = &mut options.detectors;
+        for (slot, v) in [
+            (&mut enabled.email, d.email),
+            (&mut enabled.ip, d.ip),
+            (&mut enabled.uuid, d.uuid),
+            (&mut enabled.jwt, d.jwt),
+            (&mut enabled.token, d.token),
+        ] {
+            if let Some(v) = v {
+                *slot = v;
+            }
+        }
+        if let Some(v) = d.token_min_length {
+            enabled.token_min_length = v;
+        }
+        if let Some(v) = d.ip_classes {
+            enabled.ip_classes = v;
+        }
+        options.allowlist.extend(self.allowlist.clone());
+        options.rules.extend(self.rules.iter().cloned());
+        for (key, &action) in self.policy.iter() {
+            options.policy.set(key, action);
+        }
+        if let Some(v) = self.clear_on_block {
+            options.policy.clear_on_block = v;
+        }
+        for (kind, &level) in self.severity.iter() {
+            options.severities.set(kind, level);
+        }
+    }
+
+    /// Layers `other` on top of this config: values it sets win, allowlist
+    /// entries accumulate and a rule with the same name replaces the old one.
+    pub fn merge(&mut self, other: Config) {
+        fn take<T>(slot: &mut Option<T>, v: Option<T>) {
+            if v.is_some() {
+                *slot = v;
+            }
+        }
+        take(&mut self.stable_placeholders, other.stable_placeholders);
+        take(&mut self.reassemble, other.reassemble);
+        take(&mut self.token_threshold, other.token_threshold);
+        take(&mut self.denylist, other.denylist);
+        take(&mut self.json_report, other.json_report);
+        take(&mut self.interval_ms, other.interval_ms);
+        take(&mut self.clear_on_block, other.clear_on_block);
+        self.entropy.extend(other.entropy);
+        self.policy.extend(other.policy);
+        self.severity.extend(other.severity);
+        let (d, o) = (&mut self.detectors, other.detectors);
+        take(&mut d.email, o.email);
+        take(&mut d.ip, o.ip);
+        take(&mut d.uuid, o.uuid);
+        take(&mut d.jwt, o.jwt);
+        take(&mut d.token, o.token);
+        take(&mut d.token_min_length, o.token_min_length);
+        take(&mut d.ip_classes, o.ip_classes);
+        for rule in other.rules {
+            self.rules.retain(|r| r.name != rule.name);
+            self.rules.push(rule);
+        }
+        self.allowlist.extend(other.allowlist);
+        for (name, profile) in other.profiles {
+            match self.profiles.get_mut(&name) {
+                Some(p) => {
+                    if profile.extends.is_some() {
+                        p.extends = profile.extends;
+                    }
+                    p.config.merge(profile.config);
+                }
+                None => {
+                    self.profiles.insert(name, profile);
+                }
+            }
+        }
+    }
+
+    /// The settings of profile `name`, merged with the profiles it extends.
+    /// Merge the result onto this config to select the profile.
+    pub fn profile(&self, name: &str) -> Result<Config, ConfigError> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut next = Some(name);
+        while let Some(n) = next {
+            if chain.contains(&n) {
+                chain.push(n);
+                return Err(ConfigError::new(format!(
+                    "Profile inheritance cycle: {}",
+                    chain.join(" -> ")
+                )));
+            }
+            let profile = self.profiles.get(n).ok_or_else(|| {
+                let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                let hint = match suggest(n, &known) {
+                    Some(s) => format!(" (did you mean '{}'?)", s),
+                    None if known.is_empty() => " (no profiles are defined)".to_string(),
+                    None => format!(" (defined: {})", known.join(", ")),
+                };
+                let message = match chain.last() {
+                    Some(child) => {
+                        format!(
+                            "Profile '{}' extends unknown profile '{}'{}",
+                            child, n, hint
+                        )
+                    }
+                    None => format!("Unknown profile '{}'{}", n, hint),
+                };
+                ConfigError::new(message)
+            })?;
+            chain.push(n);
+            next = profile.extends.as_deref();
+        }
+        let mut out = Config::default();
+        for n in chain.iter().rev() {
+            out.merge(self.profiles[*n].config.clone());
+        }
+        Ok(out)
+    }
+
+    /// The built-in values of every setting a config can change.
+    pub fn defaults() -> Config {
+        let options = ScrubOptions::default();
+        let d = &options.detectors;
+        Config {
+            stable_placeholders: Some(options.stable_placeholders),
+            reassemble: Some(options.reassemble),
+            token_threshold: Some(DEFAULT_TOKEN_THRESHOLD),
+            entropy: Alphabet::ALL
+                .iter()
+                .map(|&a| (a, options.entropy.get(a)))
+                .collect(),
+            detectors: DetectorConfig {
+                email: Some(d.email),
+                ip: Some(d.ip),
+                uuid: Some(d.uuid),
+                jwt: Some(d.jwt),
+                token: Some(d.token),
+                token_min_length: Some(d.token_min_length),
+                ip_classes: Some(d.ip_classes),
+            },
+            json_report: Some(false),
+            interval_ms: Some(DEFAULT_INTERVAL_MS),
+            policy: KINDS
+                .iter()
+                .map(|k| (k.to_string(), Action::default()))
+                .collect(),
+            clear_on_block: Some(options.policy.clear_on_block),
+            severity: KINDS
+                .iter()
+                .map(|k| (k.to_string(), default_severity(k)))
+                .collect(),
+            ..Config::default()
+        }
+    }
+
+    /// The settings this config makes, as flat `key=value` pairs.
+    pub fn entries(&self) -> Vec<(String, String)> {
+        let mut out: Vec<(String, String)> = Vec::new();
+        let mut push = |key: &str, value: Option<String>| {
+            if let Some(v) = value {
+                out.push((key.to_string(), v));
+            }
+        };
+        let d = &self.detectors;
+        push(
+            "stable_placeholders",
+            self.stable_placeholders.map(|v| v.to_string()),
+        );
+        push("reassemble", self.reassemble.map(|v| v.to_string()));
+        push(
+            "token_threshold",
+            self.token_threshold.map(|v| v.to_string()),
+        );
+        push("json_report", self.json_report.map(|v| v.to_string()));
+        push("interval_ms", self.interval_ms.map(|v| v.to_string()));
+        push(
+            "denylist",
+            self.denylist.as_ref().map(|p| p.display().to_string()),
+        );
+        for (name, v) in [
+            ("email", d.email),
+            ("ip", d.ip),
+            ("uuid", d.uuid),
+            ("jwt", d.jwt),
+            ("token", d.token),
+        ] {
+            push(
+                &format!("detectors.{}.enabled", name),
+                v.map(|v| v.to_string()),
+            );
+        }
+        push(
+            "detectors.ip.classes",
+            d.ip_classes.map(|c| c.names().join(",")),
+        );
+        push(
+            "detectors.token.min_length",
+            d.token_min_length.map(|v| v.to_string()),
+        );
+        for (alphabet, bits) in self.entropy.iter() {
+            push(
+                &format!("entropy_{}", alphabet.name()),
+                Some(bits.to_string()),
+            );
+        }
+        for rule in self.rules.iter() {
+            for (attr, value) in rule.to_config() {
+                push(&format!("rule.{}.{}", rule.name, attr), Some(value));
+            }
+        }
+        for entry in self.allowlist.entries() {
+            let (key, value) = entry.to_config();
+            push(key, Some(value));
+        }
+        for (key, action) in self.policy.iter() {
+            push(&format!("policy.{}", key), Some(action.name().to_string()));
+        }
+        push(
+            "policy.on_block",
+            self.clear_on_block.map(|v| on_block_name(v).to_string()),
+        );
+        for (kind, level) in self.severity.iter() {
+            push(&format!("severity.{}", kind), Some(level.to_string()));
+        }
+        for (name, profile) in self.profiles.iter() {
+            let prefix = format!("profile.{}.", name);
+            push(&format!("{}extends", prefix), profile.extends.clone());
+            for (key, value) in profile.config.entries() {
+                push(&format!("{}{}", prefix, key), Some(value));
+            }
+        }
+        out
+    }
 }
 
 #[derive(Debug)]
 pub struct ConfigError {
     pub message: String,
+    /// The line the error points at, if any.
+    pub line: Option<usize>,
+    /// The file `line` is in, when that is not the config file itself (a
+    /// denylist, say).
+    pub file: Option<PathBuf>,
 }
 
 impl std::fmt::Display for ConfigError {
@@ -21,65 +338,786 @@ impl std::fmt::Display for ConfigError {
 
 impl std::error::Error for ConfigError {}
 
+impl ConfigError {
+    pub fn new(message: String) -> ConfigError {
+        ConfigError {
+            message,
+            line: None,
+            file: None,
+        }
+    }
+
+    /// An error at `line`, which `message` already names.
+    pub fn at(line: usize, message: String) -> ConfigError {
+        ConfigError {
+            line: Some(line),
+            ..ConfigError::new(message)
+        }
+    }
+
+    /// The same error, pointing into `file` rather than the config file.
+    fn in_file(self, file: &Path) -> ConfigError {
+        ConfigError {
+            file: Some(file.to_path_buf()),
+            ..self
+        }
+    }
+}
+
+/// Orders errors by file, the config file first, then by line; errors
+/// without a line go last.
+fn sort_by_line(errors: &mut [ConfigError]) {
+    errors.sort_by(|a, b| {
+        let key = |e: &ConfigError| (e.file.clone(), e.line.unwrap_or(usize::MAX));
+        key(a).cmp(&key(b))
+    });
+}
+
 pub fn load_config(path: &Path) -> Result<Config, ConfigError> {
-    let content = fs::read_to_string(path).map_err(|e| ConfigError {
-        message: format!("Failed to read config: {}", e),
+    read_config(path).map_err(|mut errors| errors.remove(0))
+}
+
+/// Loads a config file and checks everything it refers to: custom regexes,
+/// profile inheritance and denylist files. Every error is reported, not just
+/// the first.
+pub fn validate_config(path: &Path) -> Result<Config, Vec<ConfigError>> {
+    let cfg = read_config(path).map_err(|mut errors| {
+        sort_by_line(&mut errors);
+        errors
     })?;
+    let mut errors = Vec::new();
+    for name in cfg.profiles.keys() {
+        if let Err(e) = cfg.profile(name) {
+            if !errors.iter().any(|x: &ConfigError| x.message == e.message) {
+                errors.push(e);
+            }
+        }
+    }
+    let profiles = cfg.profiles.values().map(|p| &p.config);
+    for list in std::iter::once(&cfg)
+        .chain(profiles)
+        .filter_map(|c| c.denylist.as_ref())
+    {
+        if let Err(e) = load_denylist(list).and_then(Denylist::new) {
+            errors.push(e.in_file(list));
+        }
+    }
+    sort_by_line(&mut errors);
+    match errors.is_empty() {
+        true => Ok(cfg),
+        false => Err(errors),
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config, Vec<ConfigError>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| vec![ConfigError::new(format!("Failed to read config: {}", e))])?;
 
-    parse_config(&content)
+    let mut cfg = if is_toml(path, &content) {
+        toml_format::parse_toml_config(&content)?
+    } else {
+        parse_config_all(&content)?
+    };
+    if let Some(dir) = path.parent() {
+        let profiles = cfg.profiles.values_mut().map(|p| &mut p.config);
+        for list in std::iter::once(&mut cfg.denylist)
+            .chain(profiles.map(|c| &mut c.denylist))
+            .flatten()
+        {
+            if list.is_relative() {
+                *list = dir.join(&*list);
+            }
+        }
+    }
+    Ok(cfg)
 }
 
-fn parse_config(input: &str) -> Result<Config, ConfigError> {
-    let mut cfg = Config::default();
+/// TOML files are recognised by their extension or by a `[section]` header;
+/// anything else is read as the flat `key=value` format.
+fn is_toml(path: &Path, content: &str) -> bool {
+    path.extension().is_some_and(|e| e == "toml")
+        || content.lines().any(|l| l.trim_start().starts_with('['))
+}
+
+/// Loads a denylist file: one term per line, optionally followed by
+/// `|LABEL` to redact it as `<LABEL>` instead of `<TERM>`.
+pub fn load_denylist(path: &Path) -> Result<Vec<(String, Option<String>)>, ConfigError> {
+    let content = fs::read_to_string(path).map_err(|e| {
+        ConfigError::new(format!("Failed to read denylist {}: {}", path.display(), e))
+    })?;
+    parse_denylist(&content).map_err(|e| e.in_file(path))
+}
+
+fn parse_denylist(input: &str) -> Result<Vec<(String, Option<String>)>, ConfigError> {
+    let mut entries = Vec::new();
     for (i, raw) in input.lines().enumerate() {
         let line = raw.trim();
         if line.is_empty() || line.starts_with('#') {
             continue;
         }
-        let mut parts = line.splitn(2, '=');
-        let key = parts.next().unwrap().trim();
-        let value = parts.next().unwrap_or("").trim();
-        if value.is_empty() {
-            return Err(ConfigError {
-                message: format!("Config parse error at line {}: missing value", i + 1),
-            });
-        }
-        match key {
-            "stable_placeholders" => {
-                cfg.stable_placeholders = Some(parse_bool(value, i + 1)?);
+        let (term, label) = match line.rsplit_once('|') {
+            Some((t, l)) => (t.trim(), Some(l.trim().to_ascii_uppercase())),
+            None => (line, None),
+        };
+        if term.is_empty() {
+            return Err(ConfigError::at(
+                i + 1,
+                format!("Denylist parse error at line {}: missing term", i + 1),
+            ));
+        }
+        if let Some(l) = label.as_deref() {
+            if l.is_empty() || !l.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(ConfigError::at(
+                    i + 1,
+                    format!(
+                        "Invalid denylist label '{}' at line {} (use letters, digits and _)",
+                        l,
+                        i + 1
+                    ),
+                ));
             }
-            "json_report" => {
-                cfg.json_report = Some(parse_bool(value, i + 1)?);
+        }
+        entries.push((term.to_string(), label));
+    }
+    Ok(entries)
+}
+
+/// `$XDG_CONFIG_HOME/scrubby` (or `~/.config/scrubby`), if it can be resolved.
+pub fn config_dir() -> Option<PathBuf> {
+    let mut path = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let mut p = PathBuf::from(std::env::var_os("HOME")?);
+            p.push(".config");
+            p
+        }
+    };
+    path.push("scrubby");
+    Some(path)
+}
+
+/// Loads the user-wide allowlist at `<config_dir>/allowlist`, which holds the
+/// same `allow*` keys as a config file. A missing file is an empty allowlist.
+pub fn load_global_allowlist() -> Result<Allowlist, ConfigError> {
+    let path = match config_dir() {
+        Some(p) => p.join("allowlist"),
+        None => return Ok(Allowlist::default()),
+    };
+    let content = match fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Allowlist::default()),
+        Err(e) => {
+            return Err(ConfigError::new(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )))
+        }
+    };
+    parse_allowlist(&content)
+}
+
+/// Appends `entries` to the user-wide allowlist, creating it if needed, and
+/// returns its path.
+pub fn append_global_allowlist(entries: &[AllowEntry]) -> Result<PathBuf, ConfigError> {
+    let path = config_dir()
+        .ok_or_else(|| {
+            ConfigError::new("Cannot locate the config directory (HOME is not set)".to_string())
+        })?
+        .join("allowlist");
+    let mut lines = String::new();
+    for entry in entries {
+        let (key, value) = entry.to_config();
+        lines.push_str(&format!("{}={}\n", key, value));
+    }
+    let write = || -> std::io::Result<()> {
+        fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        std::io::Write::write_all(&mut file, lines.as_bytes())
+    };
+    write().map_err(|e| ConfigError::new(format!("Failed to update {}: {}", path.display(), e)))?;
+    Ok(path)
+}
+
+fn parse_allowlist(input: &str) -> Result<Allowlist, ConfigError> {
+    let mut allowlist = Allowlist::default();
+    for (i, raw) in input.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = split_line(line, i + 1)?;
+        match parse_allow_entry(key, value, i + 1)? {
+            Some(entry) => allowlist.push(entry),
+            None => {
+                return Err(ConfigError::at(
+                    i + 1,
+                    format!("Unknown allowlist key '{}' at line {}", key, i + 1),
+                ))
             }
-            "interval_ms" => {
-                cfg.interval_ms = Some(parse_u64(value, i + 1)?);
+        }
+    }
+    Ok(allowlist)
+}
+
+const FLAT_KEYS: [&str; 10] = [
+    "stable_placeholders",
+    "reassemble",
+    "token_threshold",
+    "denylist",
+    "json_report",
+    "interval_ms",
+    "allow",
+    "allow_regex",
+    "allow_domain",
+    "allow_sha256",
+];
+
+const DETECTOR_KEYS: [&str; 8] = [
+    "email.enabled",
+    "ip.enabled",
+    "ip.classes",
+    "uuid.enabled",
+    "jwt.enabled",
+    "token.enabled",
+    "token.min_length",
+    "token.threshold",
+];
+
+/// Whether `key` names a setting, whatever its value: a flat, `entropy_*`,
+/// `detectors.*`, `policy.*` or `severity.*` key, a rule attribute, or any of
+/// these in a profile.
+pub fn is_config_key(key: &str) -> bool {
+    if let Some(rest) = key.strip_prefix("profile.") {
+        return match rest.split_once('.') {
+            Some((name, key)) => {
+                validate_profile_name(name).is_ok()
+                    && (key == "extends" || (!key.starts_with("profile.") && is_config_key(key)))
             }
-            _ => {
-                return Err(ConfigError {
-                    message: format!("Unknown config key '{}' at line {}", key, i + 1),
-                });
+            None => false,
+        };
+    }
+    if let Some(rest) = key.strip_prefix("rule.") {
+        return rest
+            .rsplit_once('.')
+            .is_some_and(|(name, attr)| !name.is_empty() && rules::ATTRIBUTES.contains(&attr));
+    }
+    if let Some(rest) = key.strip_prefix("detectors.") {
+        return DETECTOR_KEYS.contains(&rest)
+            || rest.strip_prefix("token.").is_some_and(is_entropy_key);
+    }
+    if let Some(rest) = key.strip_prefix("policy.") {
+        return rest == "on_block"
+            || KINDS.contains(&rest)
+            || rest.strip_prefix("rule.").is_some_and(|n| !n.is_empty());
+    }
+    if let Some(kind) = key.strip_prefix("severity.") {
+        return KINDS.contains(&kind);
+    }
+    FLAT_KEYS.contains(&key) || is_entropy_key(key)
+}
+
+fn is_entropy_key(key: &str) -> bool {
+    key.strip_prefix("entropy_")
+        .is_some_and(|name| Alphabet::from_name(name).is_some())
+}
+
+/// The known name closest to `name`, if it is near enough to be a typo.
+fn suggest<'a>(name: &str, known: &[&'a str]) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(1);
+    known
+        .iter()
+        .map(|k| (edit_distance(name, k), *k))
+        .filter(|&(d, _)| d <= limit)
+        .min_by_key(|&(d, _)| d)
+        .map(|(_, k)| k)
+}
+
+fn did_you_mean(name: &str, known: &[&str]) -> String {
+    match suggest(name, known) {
+        Some(s) => format!(" (did you mean '{}'?)", s),
+        None => String::new(),
+    }
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+    row[b.len()]
+}
+
+/// A `key=value` line with its line number.
+type ConfigLine<'a> = (usize, &'a str, &'a str);
+
+fn parse_config(input: &str) -> Result<Config, ConfigError> {
+    parse_config_all(input).map_err(|mut errors| errors.remove(0))
+}
+
+/// Like [`parse_config`], but carries on past errors and returns all of them.
+fn parse_config_all(input: &str) -> Result<Config, Vec<ConfigError>> {
+    let mut errors = Vec::new();
+    let mut lines = Vec::new();
+    for (i, raw) in input.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match split_line(line, i + 1) {
+            Ok((key, value)) => lines.push((i + 1, key, value)),
+            Err(e) => errors.push(e),
+        }
+    }
+    let cfg = parse_lines(&lines, true, &mut errors);
+    sort_by_line(&mut errors);
+    match errors.is_empty() {
+        true => Ok(cfg),
+        false => Err(errors),
+    }
+}
+
+/// Parses `(line, key, value)` entries, adding any problems to `errors`;
+/// `profile.<name>.<key>` entries are parsed the same way into profiles when
+/// `allow_profiles` is set.
+fn parse_lines(
+    lines: &[ConfigLine],
+    allow_profiles: bool,
+    errors: &mut Vec<ConfigError>,
+) -> Config {
+    let mut cfg = Config::default();
+    let mut drafts: Vec<RuleDraft> = Vec::new();
+    let mut profiles: BTreeMap<&str, (Option<String>, Vec<ConfigLine>)> = BTreeMap::new();
+    for &(line, key, value) in lines {
+        if let Some(rest) = key.strip_prefix("profile.") {
+            match split_profile_key(rest, line, allow_profiles) {
+                Ok((name, "extends")) => {
+                    profiles.entry(name).or_default().0 = Some(value.to_string())
+                }
+                Ok((name, key)) => profiles.entry(name).or_default().1.push((line, key, value)),
+                Err(e) => errors.push(e),
             }
+            continue;
+        }
+        if let Err(e) = parse_line(&mut cfg, &mut drafts, line, key, value) {
+            errors.push(e);
         }
     }
-    Ok(cfg)
+    for draft in drafts {
+        match draft.build() {
+            Ok(rule) => cfg.rules.push(rule),
+            Err((message, line)) => errors.push(ConfigError::at(
+                line,
+                format!("{} at line {}", message, line),
+            )),
+        }
+    }
+    for (name, (extends, lines)) in profiles {
+        let profile = Profile {
+            extends,
+            config: parse_lines(&lines, false, errors),
+        };
+        cfg.profiles.insert(name.to_string(), profile);
+    }
+    cfg
+}
+
+/// Splits the part of a `profile.<name>.<key>` key after `profile.`.
+fn split_profile_key(
+    rest: &str,
+    line: usize,
+    allow_profiles: bool,
+) -> Result<(&str, &str), ConfigError> {
+    if !allow_profiles {
+        return Err(ConfigError::at(
+            line,
+            format!("Profiles cannot be nested at line {}", line),
+        ));
+    }
+    let (name, key) = rest.split_once('.').ok_or_else(|| {
+        ConfigError::at(
+            line,
+            format!(
+                "Invalid profile key 'profile.{}' at line {} (expected profile.<name>.<key>)",
+                rest, line
+            ),
+        )
+    })?;
+    validate_profile_name(name)
+        .map_err(|m| ConfigError::at(line, format!("{} at line {}", m, line)))?;
+    Ok((name, key))
+}
+
+fn parse_line(
+    cfg: &mut Config,
+    drafts: &mut Vec<RuleDraft>,
+    line: usize,
+    key: &str,
+    value: &str,
+) -> Result<(), ConfigError> {
+    if let Some(entry) = parse_allow_entry(key, value, line)? {
+        cfg.allowlist.push(entry);
+        return Ok(());
+    }
+    if let Some(rest) = key.strip_prefix("rule.") {
+        return parse_rule_line(drafts, rest, value, line);
+    }
+    if let Some(rest) = key.strip_prefix("detectors.") {
+        return parse_detector_line(cfg, rest, value, line);
+    }
+    if let Some(rest) = key.strip_prefix("policy.") {
+        return parse_policy_line(cfg, rest, value, line);
+    }
+    if let Some(kind) = key.strip_prefix("severity.") {
+        if !KINDS.contains(&kind) {
+            let known: Vec<String> = KINDS.iter().map(|k| format!("severity.{}", k)).collect();
+            let known: Vec<&str> = known.iter().map(String::as_str).collect();
+            return Err(ConfigError::at(
+                line,
+                format!(
+                    "Unknown config key '{}' at line {}{}",
+                    key,
+                    line,
+                    did_you_mean(key, &known)
+                ),
+            ));
+        }
+        cfg.severity
+            .insert(kind.to_string(), parse_severity(value, line)?);
+        return Ok(());
+    }
+    match key {
+        "stable_placeholders" => {
+            cfg.stable_placeholders = Some(parse_bool(value, line)?);
+        }
+        "reassemble" => {
+            cfg.reassemble = Some(parse_bool(value, line)?);
+        }
+        "token_threshold" => {
+            cfg.token_threshold = Some(parse_score(value, line)?);
+        }
+        "denylist" => {
+            cfg.denylist = Some(PathBuf::from(value));
+        }
+        "json_report" => {
+            cfg.json_report = Some(parse_bool(value, line)?);
+        }
+        "interval_ms" => {
+            cfg.interval_ms = Some(parse_u64(value, line)?);
+        }
+        _ if key.starts_with("entropy_") => {
+            cfg.entropy.push(parse_entropy(key, value, line)?);
+        }
+        _ => {
+            return Err(ConfigError::at(
+                line,
+                format!(
+                    "Unknown config key '{}' at line {}{}",
+                    key,
+                    line,
+                    did_you_mean(key, &FLAT_KEYS)
+                ),
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn parse_policy_line(
+    cfg: &mut Config,
+    key: &str,
+    value: &str,
+    line: usize,
+) -> Result<(), ConfigError> {
+    if key == "on_block" {
+        cfg.clear_on_block = Some(parse_on_block(value, line)?);
+        return Ok(());
+    }
+    let valid = match key.strip_prefix("rule.") {
+        Some(name) => !name.is_empty(),
+        None => KINDS.contains(&key),
+    };
+    if !valid {
+        let known: Vec<String> = KINDS
+            .iter()
+            .chain(&["on_block"])
+            .map(|k| format!("policy.{}", k))
+            .collect();
+        let known: Vec<&str> = known.iter().map(String::as_str).collect();
+        let key = format!("policy.{}", key);
+        return Err(ConfigError::at(
+            line,
+            format!(
+                "Unknown config key '{}' at line {}{}",
+                key,
+                line,
+                did_you_mean(&key, &known)
+            ),
+        ));
+    }
+    let action = parse_action(value, line)?;
+    cfg.policy.insert(key.to_string(), action);
+    Ok(())
+}
+
+fn parse_action(value: &str, line: usize) -> Result<Action, ConfigError> {
+    Action::from_name(value).ok_or_else(|| {
+        ConfigError::at(
+            line,
+            format!(
+                "Invalid policy action '{}' at line {} (expected redact, warn, block or allow)",
+                value, line
+            ),
+        )
+    })
+}
+
+fn parse_severity(value: &str, line: usize) -> Result<Severity, ConfigError> {
+    Severity::from_name(value).ok_or_else(|| {
+        ConfigError::at(
+            line,
+            format!(
+                "Invalid severity '{}' at line {} (expected low, medium, high or critical)",
+                value, line
+            ),
+        )
+    })
+}
+
+fn parse_on_block(value: &str, line: usize) -> Result<bool, ConfigError> {
+    match value {
+        "keep" => Ok(false),
+        "clear" => Ok(true),
+        _ => Err(ConfigError::at(
+            line,
+            format!(
+                "Invalid on_block value '{}' at line {} (expected keep or clear)",
+                value, line
+            ),
+        )),
+    }
+}
+
+fn on_block_name(clear: bool) -> &'static str {
+    if clear {
+        "clear"
+    } else {
+        "keep"
+    }
+}
+
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "Invalid profile name '{}' (use letters, digits, _ and -)",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn parse_detector_line(
+    cfg: &mut Config,
+    key: &str,
+    value: &str,
+    line: usize,
+) -> Result<(), ConfigError> {
+    let d = &mut cfg.detectors;
+    match key {
+        "email.enabled" => d.email = Some(parse_bool(value, line)?),
+        "ip.enabled" => d.ip = Some(parse_bool(value, line)?),
+        "uuid.enabled" => d.uuid = Some(parse_bool(value, line)?),
+        "jwt.enabled" => d.jwt = Some(parse_bool(value, line)?),
+        "token.enabled" => d.token = Some(parse_bool(value, line)?),
+        "token.min_length" => d.token_min_length = Some(parse_u64(value, line)? as usize),
+        "token.threshold" => cfg.token_threshold = Some(parse_score(value, line)?),
+        "ip.classes" => {
+            let mut classes = IpClasses::none();
+            for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+                if !classes.enable(name) {
+                    return Err(ConfigError::at(
+                        line,
+                        format!(
+                            "Unknown IP class '{}' at line {} (expected one of: {})",
+                            name,
+                            line,
+                            IpClasses::NAMES.join(", ")
+                        ),
+                    ));
+                }
+            }
+            d.ip_classes = Some(classes);
+        }
+        _ if key.starts_with("token.entropy_") => {
+            cfg.entropy
+                .push(parse_entropy(&key["token.".len()..], value, line)?);
+        }
+        _ => {
+            return Err(ConfigError::at(
+                line,
+                format!(
+                    "Unknown config key 'detectors.{}' at line {}{}",
+                    key,
+                    line,
+                    did_you_mean(key, &DETECTOR_KEYS)
+                ),
+            ))
+        }
+    }
+    Ok(())
+}
+
+fn parse_entropy(key: &str, value: &str, line: usize) -> Result<(Alphabet, f64), ConfigError> {
+    let alphabet = Alphabet::from_name(&key["entropy_".len()..]).ok_or_else(|| {
+        ConfigError::at(
+            line,
+            format!(
+                "Unknown alphabet in '{}' at line {} (expected one of: {})",
+                key,
+                line,
+                alphabet_names()
+            ),
+        )
+    })?;
+    Ok((alphabet, parse_bits(value, alphabet, line)?))
+}
+
+fn parse_rule_line(
+    drafts: &mut Vec<RuleDraft>,
+    key: &str,
+    value: &str,
+    line: usize,
+) -> Result<(), ConfigError> {
+    let at_line = |message: String| ConfigError::at(line, format!("{} at line {}", message, line));
+    let (name, attr) = key.rsplit_once('.').ok_or_else(|| {
+        at_line(format!(
+            "Invalid rule key 'rule.{}' (expected rule.<name>.<attribute>)",
+            key
+        ))
+    })?;
+    let idx = match drafts.iter().position(|d| d.name == name) {
+        Some(idx) => idx,
+        None => {
+            drafts.push(RuleDraft::new(name, line).map_err(at_line)?);
+            drafts.len() - 1
+        }
+    };
+    drafts[idx].set(attr, value, line).map_err(at_line)
+}
+
+fn split_line(line: &str, lineno: usize) -> Result<(&str, &str), ConfigError> {
+    let mut parts = line.splitn(2, '=');
+    let key = parts.next().unwrap().trim();
+    let value = parts.next().unwrap_or("").trim();
+    if value.is_empty() {
+        return Err(ConfigError::at(
+            lineno,
+            format!("Config parse error at line {}: missing value", lineno),
+        ));
+    }
+    Ok((key, value))
+}
+
+fn parse_allow_entry(
+    key: &str,
+    value: &str,
+    line: usize,
+) -> Result<Option<AllowEntry>, ConfigError> {
+    AllowEntry::from_config(key, value)
+        .map_err(|e| ConfigError::at(line, format!("{} at line {}", e, line)))
 }
 
 fn parse_bool(value: &str, line: usize) -> Result<bool, ConfigError> {
     match value.to_ascii_lowercase().as_str() {
         "true" | "1" | "yes" => Ok(true),
         "false" | "0" | "no" => Ok(false),
-        _ => Err(ConfigError {
-            message: format!("Invalid boolean '{}' at line {}", value, line),
-        }),
+        _ => Err(ConfigError::at(
+            line,
+            format!("Invalid boolean '{}' at line {}", value, line),
+        )),
     }
 }
 
 fn parse_u64(value: &str, line: usize) -> Result<u64, ConfigError> {
-    value.parse::<u64>().map_err(|_| ConfigError {
-        message: format!("Invalid integer '{}' at line {}", value, line),
+    value.parse::<u64>().map_err(|_| {
+        ConfigError::at(
+            line,
+            format!("Invalid integer '{}' at line {}", value, line),
+        )
     })
 }
 
+fn parse_score(value: &str, line: usize) -> Result<f64, ConfigError> {
+    check_score(value.parse().unwrap_or(f64::NAN), value, line)
+}
+
+/// Accepts a score from 0.0 to 1.0; `shown` is the value as written.
+fn check_score(v: f64, shown: impl std::fmt::Display, line: usize) -> Result<f64, ConfigError> {
+    match (0.0..=1.0).contains(&v) {
+        true => Ok(v),
+        false => Err(ConfigError::at(
+            line,
+            format!(
+                "Invalid score '{}' at line {} (expected 0.0 to 1.0)",
+                shown, line
+            ),
+        )),
+    }
+}
+
+fn parse_bits(value: &str, alphabet: Alphabet, line: usize) -> Result<f64, ConfigError> {
+    check_bits(value.parse().unwrap_or(f64::NAN), value, alphabet, line)
+}
+
+/// Accepts an entropy floor `alphabet` can reach; `shown` is the value as
+/// written.
+fn check_bits(
+    v: f64,
+    shown: impl std::fmt::Display,
+    alphabet: Alphabet,
+    line: usize,
+) -> Result<f64, ConfigError> {
+    match (0.0..=alphabet.bits()).contains(&v) {
+        true => Ok(v),
+        false => Err(ConfigError::at(
+            line,
+            format!(
+                "Invalid entropy '{}' at line {} (expected 0.0 to {:.2} bits for {})",
+                shown,
+                line,
+                alphabet.bits(),
+                alphabet.name()
+            ),
+        )),
+    }
+}
+
+fn alphabet_names() -> String {
+    Alphabet::ALL
+        .iter()
+        .map(|a| a.name())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -92,4 +1130,227 @@ mod tests {
         assert_eq!(cfg.json_report, Some(false));
         assert_eq!(cfg.interval_ms, Some(500));
     }
+
+    #[test]
+    fn rejects_out_of_range_threshold() {
+        assert_eq!(
+            parse_config("token_threshold=0.75\n")
+                .unwrap()
+                .token_threshold,
+            Some(0.75)
+        );
+        let err = parse_config("token_threshold=2\n").unwrap_err();
+        assert!(err.message.contains("line 1"));
+    }
+
+    #[test]
+    fn parses_entropy_thresholds() {
+        let cfg = parse_config("entropy_hex=3.2\nentropy_base64=4.5\n").unwrap();
+        assert_eq!(
+            cfg.entropy,
+            vec![(Alphabet::Hex, 3.2), (Alphabet::Base64, 4.5)]
+        );
+        let err = parse_config("entropy_hex=5\n").unwrap_err();
+        assert!(err.message.contains("4.00 bits for hex"));
+        let err = parse_config("entropy_morse=1\n").unwrap_err();
+        assert!(err.message.contains("Unknown alphabet"));
+    }
+
+    #[test]
+    fn collects_allowlist_entries() {
+        let cfg = parse_config("allow=support@acme.io\nallow_domain=example.com\n").unwrap();
+        assert!(cfg.allowlist.allows("support@acme.io"));
+        assert!(cfg.allowlist.allows("a@example.com"));
+        let err = parse_config("json_report=true\nallow_regex=(\n").unwrap_err();
+        assert!(err.message.contains("at line 2"));
+        let err = parse_allowlist("interval_ms=5\n").unwrap_err();
+        assert!(err.message.contains("Unknown allowlist key"));
+    }
+
+    #[test]
+    fn parses_denylist_with_labels() {
+        let entries = parse_denylist("# customers\nGlobex|customer\nProject Falcon\n").unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                ("Globex".to_string(), Some("CUSTOMER".to_string())),
+                ("Project Falcon".to_string(), None),
+            ]
+        );
+        let err = parse_denylist("ok\nbad|<x>\n").unwrap_err();
+        assert!(err.message.contains("line 2"));
+    }
+
+    #[test]
+    fn parses_custom_rules() {
+        let cfg = parse_config(
+            "rule.aws.regex=(AKIA[0-9A-Z]{16})\nrule.aws.group=1\nrule.aws.label=aws_key\nrule.aws.keywords=aws\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.rules.len(), 1);
+        assert_eq!(cfg.rules[0].label, "AWS_KEY");
+        assert_eq!(cfg.rules[0].group, 1);
+
+        let err = parse_config("rule.aws.label=x\n").unwrap_err();
+        assert_eq!(err.message, "Rule 'aws' has no regex at line 1");
+        let err = parse_config("rule.aws.regex=a\nrule.aws.color=red\n").unwrap_err();
+        assert!(err.message.contains("Unknown rule attribute 'color'"));
+        assert!(err.message.ends_with("at line 2"));
+        let err = parse_config("rule.aws=x\n").unwrap_err();
+        assert!(err.message.contains("expected rule.<name>.<attribute>"));
+    }
+
+    #[test]
+    fn parses_detector_settings() {
+        let cfg = parse_config(
+            "detectors.uuid.enabled=false\ndetectors.token.min_length=24\ndetectors.token.threshold=0.7\ndetectors.ip.classes=public, private\n",
+        )
+        .unwrap();
+        let mut opts = ScrubOptions::default();
+        cfg.apply(&mut opts);
+        assert!(!opts.detectors.uuid);
+        assert!(opts.detectors.email);
+        assert_eq!(opts.detectors.token_min_length, 24);
+        assert_eq!(opts.token_threshold, 0.7);
+        assert!(opts.detectors.ip_classes.private);
+        assert!(!opts.detectors.ip_classes.loopback);
+
+        let err = parse_config("detectors.ip.classes=public,lan\n").unwrap_err();
+        assert!(err.message.contains("Unknown IP class 'lan' at line 1"));
+        let err = parse_config("detectors.mac.enabled=true\n").unwrap_err();
+        assert!(err
+            .message
+            .contains("Unknown config key 'detectors.mac.enabled'"));
+    }
+
+    #[test]
+    fn resolves_profile_inheritance() {
+        let cfg = parse_config(
+            "profile.base.detectors.uuid.enabled=false\nprofile.base.allow=a@acme.io\nprofile.logs.extends=base\nprofile.logs.detectors.ip.enabled=false\n",
+        )
+        .unwrap();
+        let logs = cfg.profile("logs").unwrap();
+        assert_eq!(logs.detectors.uuid, Some(false));
+        assert_eq!(logs.detectors.ip, Some(false));
+        assert!(logs.allowlist.allows("a@acme.io"));
+
+        let err = cfg.profile("strict").unwrap_err();
+        assert_eq!(
+            err.message,
+            "Unknown profile 'strict' (defined: base, logs)"
+        );
+        let cyclic = parse_config("profile.a.extends=b\nprofile.b.extends=a\n").unwrap();
+        assert_eq!(
+            cyclic.profile("a").unwrap_err().message,
+            "Profile inheritance cycle: a -> b -> a"
+        );
+        let err = parse_config("profile.a.profile.b.reassemble=true\n").unwrap_err();
+        assert_eq!(err.message, "Profiles cannot be nested at line 1");
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let errors = parse_config_all(
+            "reassemble=maybe\nrule.a.regex=(\ncolour=red\nprofile.p.token_threshold=9\nnovalue\n",
+        )
+        .unwrap_err();
+        let lines: Vec<&str> = errors.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Invalid boolean 'maybe' at line 1",
+                "Invalid regex for rule 'a' (unclosed group) at line 2",
+                "Unknown config key 'colour' at line 3",
+                "Invalid score '9' at line 4 (expected 0.0 to 1.0)",
+                "Config parse error at line 5: missing value",
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_profiles_and_denylist() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scrubby.conf");
+        fs::write(
+            &path,
+            "denylist=missing.txt\nprofile.a.extends=b\nprofile.b.extends=a\n",
+        )
+        .unwrap();
+        let errors = validate_config(&path).unwrap_err();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[2].message.starts_with("Failed to read denylist"));
+
+        // A denylist's line numbers are its own, not the config file's.
+        let list = dir.path().join("terms.txt");
+        fs::write(&list, "Globex\n|customer\n").unwrap();
+        fs::write(
+            &path,
+            "denylist=terms.txt\nprofile.a.extends=b\nprofile.b.extends=a\n",
+        )
+        .unwrap();
+        let errors = validate_config(&path).unwrap_err();
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].message.starts_with("Profile inheritance cycle"));
+        assert_eq!(errors[2].line, Some(2));
+        assert_eq!(errors[2].file.as_deref(), Some(list.as_path()));
+
+        fs::write(&path, "profile.a.reassemble=true\n").unwrap();
+        assert!(validate_config(&path).is_ok());
+    }
+
+    #[test]
+    fn suggests_close_keys() {
+        let err = parse_config("json_reprot=true\n").unwrap_err();
+        assert_eq!(
+            err.message,
+            "Unknown config key 'json_reprot' at line 1 (did you mean 'json_report'?)"
+        );
+        let err = parse_config("detectors.token.min_len=3\n").unwrap_err();
+        assert!(err.message.ends_with("(did you mean 'token.min_length'?)"));
+        assert_eq!(suggest("colour", &FLAT_KEYS), None);
+    }
+
+    #[test]
+    fn parses_policy_keys() {
+        let cfg = parse_config(
+            "policy.jwt=block\npolicy.rule.private-key=block\npolicy.on_block=clear\n",
+        )
+        .unwrap();
+        let mut opts = ScrubOptions::default();
+        cfg.apply(&mut opts);
+        assert_eq!(opts.policy.action("jwt", None).0, Action::Block);
+        assert_eq!(
+            opts.policy.action("custom", Some("private-key")).0,
+            Action::Block
+        );
+        assert!(opts.policy.clear_on_block);
+
+        let err = parse_config("policy.jwt=nuke\n").unwrap_err();
+        assert_eq!(
+            err.message,
+            "Invalid policy action 'nuke' at line 1 (expected redact, warn, block or allow)"
+        );
+        let err = parse_config("policy.emial=warn\n").unwrap_err();
+        assert!(err.message.ends_with("(did you mean 'policy.email'?)"));
+    }
+
+    #[test]
+    fn parses_severity_keys() {
+        let cfg = parse_config(
+            "severity.email=high\nrule.pk.regex=PRIVATE KEY\nrule.pk.severity=critical\n",
+        )
+        .unwrap();
+        let mut opts = ScrubOptions::default();
+        cfg.apply(&mut opts);
+        assert_eq!(opts.severities.get("email"), Severity::High);
+        assert_eq!(opts.rules[0].severity, Some(Severity::Critical));
+        assert_eq!(
+            parse_config("severity.jwt=severe\n").unwrap_err().message,
+            "Invalid severity 'severe' at line 1 (expected low, medium, high or critical)"
+        );
+        assert!(parse_config("severity.tokens=low\n")
+            .unwrap_err()
+            .message
+            .ends_with("(did you mean 'severity.token'?)"));
+    }
 }
@@ -0,0 +1,354 @@
+//! Finds the config layers that apply to an invocation: system, user and
+//! project files, a `--config` file and `SCRUBBY_*` environment overrides,
+//! from lowest to highest precedence.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::{config_dir, is_config_key, load_config, parse_config, Config, ConfigError};
+
+pub const SYSTEM_DIR: &str = "/etc/scrubby";
+/// Project config, looked up in the working directory and its ancestors.
+pub const PROJECT_FILE: &str = ".scrubby.toml";
+/// Names tried, in order, in the system and user config directories.
+const FILE_NAMES: [&str; 2] = ["config.toml", "config"];
+const ENV_PREFIX: &str = "SCRUBBY_";
+/// Selects a profile when `--profile` is not given.
+pub const PROFILE_VAR: &str = "SCRUBBY_PROFILE";
+
+/// Where a config layer (and so each of its values) came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    Default,
+    System(PathBuf),
+    User(PathBuf),
+    Project(PathBuf),
+    /// A file passed with `--config`.
+    Explicit(PathBuf),
+    Env(String),
+    /// The selected profile, with the profiles it extends.
+    Profile(String),
+}
+
+impl Source {
+    /// The config file, for sources that are files.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Source::System(p) | Source::User(p) | Source::Project(p) | Source::Explicit(p) => {
+                Some(p)
+            }
+            Source::Default | Source::Env(_) | Source::Profile(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Default => write!(f, "default"),
+            Source::System(p) => write!(f, "system {}", p.display()),
+            Source::User(p) => write!(f, "user {}", p.display()),
+            Source::Project(p) => write!(f, "project {}", p.display()),
+            Source::Explicit(p) => write!(f, "--config {}", p.display()),
+            Source::Env(var) => write!(f, "env {}", var),
+            Source::Profile(name) => write!(f, "profile {}", name),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub source: Source,
+    pub config: Config,
+}
+
+/// Loads every config layer that exists, lowest precedence first. Merge them
+/// with [`Config::merge`] in order to get the effective config.
+pub fn discover(explicit: Option<&Path>) -> Result<Vec<Layer>, ConfigError> {
+    let cwd = std::env::current_dir().ok();
+    discover_in(
+        Path::new(SYSTEM_DIR),
+        config_dir().as_deref(),
+        cwd.as_deref(),
+        explicit,
+        std::env::vars(),
+    )
+}
+
+fn discover_in(
+    system: &Path,
+    user: Option<&Path>,
+    cwd: Option<&Path>,
+    explicit: Option<&Path>,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Result<Vec<Layer>, ConfigError> {
+    let mut files = Vec::new();
+    if let Some(p) = file_in(system) {
+        files.push(Source::System(p));
+    }
+    if let Some(p) = user.and_then(file_in) {
+        files.push(Source::User(p));
+    }
+    if let Some(p) = cwd.and_then(project_file) {
+        files.push(Source::Project(p));
+    }
+    if let Some(p) = explicit {
+        files.push(Source::Explicit(p.to_path_buf()));
+    }
+
+    let mut layers = Vec::new();
+    for source in files {
+        let path = source.path().expect("file source");
+        let config = load_config(path).map_err(|e| ConfigError {
+            message: format!("{}: {}", path.display(), e),
+            ..e
+        })?;
+        layers.push(Layer { source, config });
+    }
+    layers.extend(env_layers(vars)?);
+    Ok(layers)
+}
+
+fn file_in(dir: &Path) -> Option<PathBuf> {
+    FILE_NAMES.iter().map(|n| dir.join(n)).find(|p| p.is_file())
+}
+
+fn project_file(cwd: &Path) -> Option<PathBuf> {
+    cwd.ancestors()
+        .map(|dir| dir.join(PROJECT_FILE))
+        .find(|p| p.is_file())
+}
+
+/// One layer per `SCRUBBY_<KEY>` variable, where `<KEY>` is a flat config key
+/// in upper case with `.` written as `__` (`SCRUBBY_DETECTORS__UUID__ENABLED`).
+/// Empty variables and those that name no config key, such as
+/// `SCRUBBY_LICENSE`, are ignored.
+fn env_layers(vars: impl IntoIterator<Item = (String, String)>) -> Result<Vec<Layer>, ConfigError> {
+    let mut vars: Vec<(String, String, String)> = vars
+        .into_iter()
+        .filter(|(_, v)| !v.is_empty())
+        .filter_map(|(var, value)| {
+            let key = var
+                .strip_prefix(ENV_PREFIX)?
+                .to_ascii_lowercase()
+                .replace("__", ".");
+            is_config_key(&key).then_some((var, key, value))
+        })
+        .collect();
+    vars.sort();
+
+    let mut layers = Vec::new();
+    for (var, key, value) in vars {
+        let config = parse_config(&format!("{}={}", key, value)).map_err(|e| {
+            ConfigError::new(format!(
+                "{} (from {})",
+                e.message.replace(" at line 1", ""),
+                var
+            ))
+        })?;
+        layers.push(Layer {
+            source: Source::Env(var),
+            config,
+        });
+    }
+    Ok(layers)
+}
+
+/// Adds the settings of profile `name` as a layer above the config files and
+/// below the environment overrides.
+pub fn select_profile(layers: &mut Vec<Layer>, name: &str) -> Result<(), ConfigError> {
+    let mut merged = Config::default();
+    for layer in layers.iter() {
+        merged.merge(layer.config.clone());
+    }
+    let layer = Layer {
+        source: Source::Profile(name.to_string()),
+        config: merged.profile(name)?,
+    };
+    let at = layers
+        .iter()
+        .position(|l| matches!(l.source, Source::Env(_)))
+        .unwrap_or(layers.len());
+    layers.insert(at, layer);
+    Ok(())
+}
+
+/// The effective value of every setting as `(key, value, source)`, starting
+/// from the built-in defaults. Later layers replace earlier values, except
+/// allowlist entries, which accumulate.
+pub fn effective_entries(layers: &[Layer]) -> Vec<(String, String, Source)> {
+    let defaults = Layer {
+        source: Source::Default,
+        config: Config::defaults(),
+    };
+    let mut out: Vec<(String, String, Source)> = Vec::new();
+    for layer in std::iter::once(&defaults).chain(layers) {
+        for rule in layer.config.rules.iter() {
+            let prefix = format!("rule.{}.", rule.name);
+            out.retain(|(k, _, _)| !k.starts_with(&prefix));
+        }
+        for (key, value) in layer.config.entries() {
+            let existing = match key.starts_with("allow") {
+                true => None,
+                false => out.iter_mut().find(|(k, _, _)| *k == key),
+            };
+            match existing {
+                Some(slot) => {
+                    slot.1 = value;
+                    slot.2 = layer.source.clone();
+                }
+                None => out.push((key, value, layer.source.clone())),
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn layers_system_user_project_and_env() {
+        let root = tempfile::tempdir().unwrap();
+        let (system, user, project) = (
+            root.path().join("etc"),
+            root.path().join("home"),
+            root.path().join("repo"),
+        );
+        let cwd = project.join("src/deep");
+        for dir in [&system, &user, &cwd] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(
+            system.join("config"),
+            "stable_placeholders=true\ninterval_ms=1000\nallow=a@acme.io\n",
+        )
+        .unwrap();
+        fs::write(
+            user.join("config.toml"),
+            "interval_ms = 900\n[allowlist]\nvalues = [\"b@acme.io\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join(PROJECT_FILE),
+            "[detectors.uuid]\nenabled = false\n",
+        )
+        .unwrap();
+
+        let layers = discover_in(
+            &system,
+            Some(&user),
+            Some(&cwd),
+            None,
+            vars(&[
+                ("SCRUBBY_INTERVAL_MS", "800"),
+                ("SCRUBBY_LICENSE", "DEV"),
+                ("SCRUBBY_REASSEMBLE", ""),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(layers.len(), 4);
+        assert_eq!(
+            layers[2].source,
+            Source::Project(project.join(PROJECT_FILE))
+        );
+
+        let mut merged = Config::default();
+        for layer in layers.iter() {
+            merged.merge(layer.config.clone());
+        }
+        assert_eq!(merged.interval_ms, Some(800));
+        assert_eq!(merged.stable_placeholders, Some(true));
+        assert_eq!(merged.detectors.uuid, Some(false));
+        assert!(merged.allowlist.allows("a@acme.io") && merged.allowlist.allows("b@acme.io"));
+
+        let entries = effective_entries(&layers);
+        let find = |key: &str| entries.iter().find(|(k, _, _)| k == key).unwrap();
+        assert_eq!(
+            find("interval_ms").2,
+            Source::Env("SCRUBBY_INTERVAL_MS".to_string())
+        );
+        assert_eq!(
+            find("stable_placeholders").2,
+            Source::System(system.join("config"))
+        );
+        assert_eq!(find("reassemble").2, Source::Default);
+        assert_eq!(entries.iter().filter(|(k, _, _)| k == "allow").count(), 2);
+    }
+
+    #[test]
+    fn env_errors_name_the_variable() {
+        let err = env_layers(vars(&[("SCRUBBY_STABLE_PLACEHOLDERS", "maybe")])).unwrap_err();
+        assert_eq!(
+            err.message,
+            "Invalid boolean 'maybe' (from SCRUBBY_STABLE_PLACEHOLDERS)"
+        );
+        let layers = env_layers(vars(&[("SCRUBBY_DETECTORS__JWT__ENABLED", "false")])).unwrap();
+        assert_eq!(layers[0].config.detectors.jwt, Some(false));
+    }
+
+    #[test]
+    fn env_ignores_variables_that_are_not_config_keys() {
+        let layers = env_layers(vars(&[
+            ("SCRUBBY_FOO", "bar"),
+            ("SCRUBBY_LICENSE", "DEV"),
+            ("SCRUBBY_PROFILE", "strict"),
+            ("SCRUBBY_PUBLIC_KEY_B64", "abc"),
+            ("SCRUBBY_POLICY__JWT", "block"),
+            ("SCRUBBY_PROFILE__CI__REASSEMBLE", "true"),
+        ]))
+        .unwrap();
+        let sources: Vec<String> = layers.iter().map(|l| l.source.to_string()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                "env SCRUBBY_POLICY__JWT",
+                "env SCRUBBY_PROFILE__CI__REASSEMBLE"
+            ]
+        );
+    }
+
+    #[test]
+    fn profile_sits_between_files_and_env() {
+        let file = parse_config(
+            "stable_placeholders=false\nprofile.base.reassemble=true\nprofile.strict.extends=base\nprofile.strict.token_threshold=0.4\nprofile.strict.interval_ms=100\n",
+        )
+        .unwrap();
+        let mut layers = vec![Layer {
+            source: Source::Explicit(PathBuf::from("s.conf")),
+            config: file,
+        }];
+        layers.extend(env_layers(vars(&[("SCRUBBY_INTERVAL_MS", "200")])).unwrap());
+        select_profile(&mut layers, "strict").unwrap();
+        assert_eq!(layers[1].source, Source::Profile("strict".to_string()));
+
+        let mut merged = Config::default();
+        for layer in layers.iter() {
+            merged.merge(layer.config.clone());
+        }
+        assert_eq!(merged.reassemble, Some(true));
+        assert_eq!(merged.token_threshold, Some(0.4));
+        assert_eq!(merged.interval_ms, Some(200));
+        assert!(select_profile(&mut layers, "strcit")
+            .unwrap_err()
+            .message
+            .contains("did you mean 'strict'?"));
+    }
+
+    #[test]
+    fn later_rule_replaces_earlier_one() {
+        let mut base = parse_config("rule.k.regex=a+\nrule.k.label=OLD\n").unwrap();
+        base.merge(parse_config("rule.k.regex=b+\n").unwrap());
+        assert_eq!(base.rules.len(), 1);
+        assert_eq!(base.rules[0].regex.as_str(), "b+");
+    }
+}
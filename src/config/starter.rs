@@ -0,0 +1,155 @@
+//! The commented config written by `scrubby config init`.
+
+use crate::detectors::{DetectorOptions, IpClasses};
+use crate::policy::{Action, Policy};
+use crate::redactor::KINDS;
+use crate::scoring::{Alphabet, EntropyThresholds, DEFAULT_TOKEN_THRESHOLD};
+use crate::severity::default_severity;
+
+use super::DEFAULT_INTERVAL_MS;
+
+/// A TOML config listing every setting with its default value. Settings are
+/// commented out, so the file changes nothing until they are edited.
+pub fn starter_config() -> String {
+    let detectors = DetectorOptions::default();
+    let entropy = EntropyThresholds::default();
+    let mut out = String::new();
+    let mut line = |s: &str| {
+        out.push_str(s);
+        out.push('\n');
+    };
+
+    line("# Scrubby config. Uncomment a setting to change it from its default.");
+    line("# Check this file with: scrubby config validate <path>");
+    line("");
+    line("# Rejoin secrets split across line wraps or string concatenation.");
+    line("#reassemble = false");
+    line("# File of terms that are always redacted, one per line, `term|LABEL`.");
+    line("#denylist = \"terms.txt\"");
+    line("# Clipboard poll interval for watch mode.");
+    line(&format!("#interval_ms = {}", DEFAULT_INTERVAL_MS));
+    line("");
+    line("[output]");
+    line("# Number placeholders per kind: <EMAIL_1>, <EMAIL_2>, ...");
+    line("#stable_placeholders = false");
+    line("#json_report = false");
+    line("");
+    for (name, enabled) in [
+        ("email", detectors.email),
+        ("uuid", detectors.uuid),
+        ("jwt", detectors.jwt),
+    ] {
+        line(&format!("[detectors.{}]", name));
+        line(&format!("#enabled = {}", enabled));
+        line("");
+    }
+    line("[detectors.ip]");
+    line(&format!("#enabled = {}", detectors.ip));
+    let classes = IpClasses::NAMES
+        .iter()
+        .map(|n| format!("\"{}\"", n))
+        .collect::<Vec<_>>()
+        .join(", ");
+    line(&format!("#classes = [{}]", classes));
+    line("");
+    line("[detectors.token]");
+    line(&format!("#enabled = {}", detectors.token));
+    line(&format!("#min_length = {}", detectors.token_min_length));
+    line("# Redact generic tokens scoring at least this (0.0 to 1.0).");
+    line(&format!("#threshold = {:?}", DEFAULT_TOKEN_THRESHOLD));
+    line("");
+    line("# Minimum entropy, in bits per character, for each alphabet.");
+    line("[detectors.token.entropy]");
+    for alphabet in Alphabet::ALL {
+        line(&format!(
+            "#{} = {:?}",
+            alphabet.name(),
+            entropy.get(alphabet)
+        ));
+    }
+    line("");
+    line("# Values that are detected but left in place.");
+    line("[allowlist]");
+    line("#values = [\"support@example.com\"]");
+    line("# Must match the whole value.");
+    line("#regexes = ['10\\.0\\.0\\.\\d+']");
+    line("# Email domains, including subdomains.");
+    line("#domains = [\"example.com\"]");
+    line("# SHA-256 of a value, from: scrubby config hash-value <value>");
+    line("#sha256 = []");
+    line("");
+    line("# Custom detectors; only regex is required.");
+    line("#[rules.aws-key]");
+    line("#regex = '\\b(AKIA[0-9A-Z]{16})\\b'");
+    line("# Capture group to redact (default: the whole match).");
+    line("#group = 1");
+    line("# Minimum entropy of the redacted text.");
+    line("#entropy = 3.0");
+    line("# Placeholder label (default: the rule name in upper case).");
+    line("#label = \"AWS_KEY\"");
+    line("# One of these must appear just before the match.");
+    line("#keywords = [\"aws\", \"amazon\"]");
+    line("# Severity of its findings (default: the `custom` severity).");
+    line("#severity = \"critical\"");
+    line("");
+    line("# What happens to each kind of finding: redact (replace with a");
+    line("# placeholder), warn (leave in place and report), block (refuse to write");
+    line("# the clipboard) or allow (leave in place silently).");
+    line("[policy]");
+    for kind in KINDS {
+        line(&format!("#{} = \"{}\"", kind, Action::default().name()));
+    }
+    line("# When blocking, leave the clipboard as it was (keep) or empty it (clear).");
+    line(&format!(
+        "#on_block = \"{}\"",
+        super::on_block_name(Policy::default().clear_on_block)
+    ));
+    line("");
+    line("# Actions for single custom rules, overriding `custom`.");
+    line("[policy.rules]");
+    line("#aws-key = \"block\"");
+    line("");
+    line("# Severity reported for each kind: low, medium, high or critical.");
+    line("[severity]");
+    for kind in KINDS {
+        line(&format!("#{} = \"{}\"", kind, default_severity(kind)));
+    }
+    line("");
+    line("# Profiles, selected with --profile <name> or SCRUBBY_PROFILE, hold any");
+    line("# of the settings above and can extend another profile.");
+    line("#[profiles.internal.detectors.ip]");
+    line("#enabled = false");
+    line("#[profiles.strict]");
+    line("#extends = \"internal\"");
+    line("#reassemble = true");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::toml_format::parse_toml_config;
+    use super::*;
+
+    #[test]
+    fn starter_config_parses_as_is_and_uncommented() {
+        let text = starter_config();
+        assert_eq!(parse_toml_config(&text).unwrap(), Default::default());
+
+        let uncommented: String = text
+            .lines()
+            .map(|l| match l.strip_prefix('#') {
+                Some(rest) if !rest.starts_with(' ') => rest,
+                _ => l,
+            })
+            .map(|l| format!("{}\n", l))
+            .collect();
+        let cfg = parse_toml_config(&uncommented).unwrap();
+        assert_eq!(cfg.rules[0].label, "AWS_KEY");
+        assert_eq!(cfg.policy["rule.aws-key"], Action::Block);
+        assert_eq!(
+            cfg.rules[0].severity,
+            Some(crate::severity::Severity::Critical)
+        );
+        assert_eq!(cfg.profile("strict").unwrap().detectors.ip, Some(false));
+    }
+}
@@ -0,0 +1,717 @@
+//! TOML config files: `[output]`, `[detectors.*]`, `[rules.<name>]`,
+//! `[allowlist]`, `[policy]` and `[severity]` sections mapped onto the same [`Config`] as the flat format.
+
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use toml::Spanned;
+use toml_edit::{ImDocument, Item, Table, TableLike};
+
+use super::{
+    check_bits, check_score, did_you_mean, parse_action, parse_allow_entry, parse_on_block,
+    parse_severity, sort_by_line, validate_profile_name, Config, ConfigError, DetectorConfig,
+    Profile,
+};
+use crate::detectors::IpClasses;
+use crate::redactor::KINDS;
+use crate::rules::RuleDraft;
+use crate::scoring::Alphabet;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlConfig {
+    reassemble: Option<bool>,
+    denylist: Option<String>,
+    interval_ms: Option<u64>,
+    output: Option<Output>,
+    detectors: Option<Detectors>,
+    rules: Option<BTreeMap<String, RuleSection>>,
+    allowlist: Option<AllowlistSection>,
+    policy: Option<PolicySection>,
+    severity: Option<BTreeMap<String, Spanned<String>>>,
+    /// Only valid inside a profile.
+    extends: Option<Spanned<String>>,
+    profiles: Option<BTreeMap<String, TomlConfig>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Output {
+    stable_placeholders: Option<bool>,
+    json_report: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Detectors {
+    email: Option<Toggle>,
+    ip: Option<IpSection>,
+    uuid: Option<Toggle>,
+    jwt: Option<Toggle>,
+    token: Option<TokenSection>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Toggle {
+    enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IpSection {
+    enabled: Option<bool>,
+    classes: Option<Vec<Spanned<String>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TokenSection {
+    enabled: Option<bool>,
+    min_length: Option<usize>,
+    threshold: Option<Spanned<f64>>,
+    entropy: Option<BTreeMap<String, Spanned<f64>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleSection {
+    regex: Option<Spanned<String>>,
+    group: Option<Spanned<usize>>,
+    entropy: Option<Spanned<f64>>,
+    label: Option<Spanned<String>>,
+    keywords: Option<Spanned<Vec<String>>>,
+    severity: Option<Spanned<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct AllowlistSection {
+    values: Vec<Spanned<String>>,
+    regexes: Vec<Spanned<String>>,
+    domains: Vec<Spanned<String>>,
+    sha256: Vec<Spanned<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicySection {
+    email: Option<Spanned<String>>,
+    ip: Option<Spanned<String>>,
+    uuid: Option<Spanned<String>>,
+    jwt: Option<Spanned<String>>,
+    token: Option<Spanned<String>>,
+    term: Option<Spanned<String>>,
+    custom: Option<Spanned<String>>,
+    on_block: Option<Spanned<String>>,
+    rules: Option<BTreeMap<String, Spanned<String>>>,
+}
+
+/// Parses a TOML config, reporting every error. A file with syntax errors goes
+/// no further; otherwise unknown keys, values of the wrong type and invalid
+/// settings are all reported together.
+pub(super) fn parse_toml_config(input: &str) -> Result<Config, Vec<ConfigError>> {
+    let doc = parse_document(input)?;
+    let mut errors = Vec::new();
+    let mut unknown = Vec::new();
+    unknown_keys(
+        doc.as_table(),
+        &mut Vec::new(),
+        input,
+        &mut errors,
+        &mut unknown,
+    );
+    // Blank out what has been reported, so the rest still gets checked.
+    let mut text = input.to_string();
+    for span in unknown {
+        blank_lines(&mut text, span);
+    }
+    let Some(mut raw) = deserialize(&mut text, &mut errors) else {
+        return Err(errors);
+    };
+    let line = |span: Range<usize>| line_of(input, span.start);
+    if let Some(extends) = raw.extends.take() {
+        errors.push(ConfigError::at(
+            line(extends.span()),
+            format!(
+                "'extends' is only allowed in a profile at line {}",
+                line(extends.span())
+            ),
+        ));
+    }
+
+    let root = doc.as_table();
+    let mut profiles = BTreeMap::new();
+    for (name, mut section) in raw.profiles.take().unwrap_or_default() {
+        let at = key_line(root, &["profiles", &name], input);
+        if let Err(m) = validate_profile_name(&name) {
+            errors.push(ConfigError::at(at, format!("{} at line {}", m, at)));
+        }
+        if section.profiles.take().is_some() {
+            errors.push(ConfigError::at(
+                at,
+                format!("Profiles cannot be nested at line {}", at),
+            ));
+        }
+        let extends = section.extends.take().map(Spanned::into_inner);
+        let config = to_config(section, &["profiles", &name], root, input, &mut errors);
+        profiles.insert(name, Profile { extends, config });
+    }
+
+    let mut cfg = to_config(raw, &[], root, input, &mut errors);
+    cfg.profiles = profiles;
+    sort_by_line(&mut errors);
+    match errors.is_empty() {
+        true => Ok(cfg),
+        false => Err(errors),
+    }
+}
+
+/// Keys allowed in the table at `path`; `None` for tables with free-form
+/// keys (rule and profile names, alphabets) or that are not tables at all.
+fn table_keys(path: &[&str]) -> Option<&'static [&'static str]> {
+    let keys: &'static [&'static str] = match path {
+        ["profiles", _, rest @ ..] => return table_keys(rest),
+        [] => &[
+            "reassemble",
+            "denylist",
+            "interval_ms",
+            "output",
+            "detectors",
+            "rules",
+            "allowlist",
+            "policy",
+            "severity",
+            "extends",
+            "profiles",
+        ],
+        ["output"] => &["stable_placeholders", "json_report"],
+        ["detectors"] => &["email", "ip", "uuid", "jwt", "token"],
+        ["detectors", "email" | "uuid" | "jwt"] => &["enabled"],
+        ["detectors", "ip"] => &["enabled", "classes"],
+        ["detectors", "token"] => &["enabled", "min_length", "threshold", "entropy"],
+        ["rules", _] => &["regex", "group", "entropy", "label", "keywords", "severity"],
+        ["severity"] => &KINDS,
+        ["allowlist"] => &["values", "regexes", "domains", "sha256"],
+        ["policy"] => &[
+            "email", "ip", "uuid", "jwt", "token", "term", "custom", "on_block", "rules",
+        ],
+        _ => return None,
+    };
+    Some(keys)
+}
+
+/// Reports every unknown key below `table`, adding the spans it covers to
+/// `spans`.
+fn unknown_keys<'a>(
+    table: &'a dyn TableLike,
+    path: &mut Vec<&'a str>,
+    input: &str,
+    errors: &mut Vec<ConfigError>,
+    spans: &mut Vec<Range<usize>>,
+) {
+    let known = table_keys(path);
+    for (key, item) in table.iter() {
+        if let Some(known) = known {
+            if !known.contains(&key) {
+                let key_span = table.key(key).and_then(|k| k.span());
+                let at = key_span.clone().map_or(1, |s| line_of(input, s.start));
+                let dotted = path.iter().chain([&key]).copied().collect::<Vec<_>>();
+                errors.push(unknown_key(&dotted.join("."), at, key, known));
+                spans.extend(key_span);
+                item_spans(item, spans);
+                continue;
+            }
+        }
+        if let Some(child) = item.as_table_like() {
+            path.push(key);
+            unknown_keys(child, path, input, errors, spans);
+            path.pop();
+        }
+    }
+}
+
+/// The spans of `item`: a value, or a table's header and all its entries.
+fn item_spans(item: &Item, spans: &mut Vec<Range<usize>>) {
+    let tables: Vec<&Table> = match item {
+        Item::Table(t) => vec![t],
+        Item::ArrayOfTables(a) => a.iter().collect(),
+        _ => {
+            spans.extend(item.span());
+            return;
+        }
+    };
+    for table in tables {
+        spans.extend(table.span());
+        for (key, child) in table.iter() {
+            spans.extend(table.key(key).and_then(|k| k.span()));
+            item_spans(child, spans);
+        }
+    }
+}
+
+/// Parses the document, or finds every syntax error in it by blanking out
+/// each offending line in turn and parsing the rest again.
+fn parse_document(input: &str) -> Result<ImDocument<&str>, Vec<ConfigError>> {
+    let mut e = match ImDocument::parse(input) {
+        Ok(doc) => return Ok(doc),
+        Err(e) => e,
+    };
+    let mut text = input.to_string();
+    let mut errors = Vec::new();
+    loop {
+        let line = e.span().map_or(1, |s| line_of(&text, s.start));
+        errors.push(ConfigError::at(
+            line,
+            format!(
+                "Config parse error at line {}: {}",
+                line,
+                e.message().trim()
+            ),
+        ));
+        if !e.span().is_some_and(|s| blank_lines(&mut text, s)) {
+            return Err(errors);
+        }
+        match ImDocument::parse(text.as_str()) {
+            Ok(_) => return Err(errors),
+            Err(next) => e = next,
+        }
+    }
+}
+
+/// Deserializes `text`, blanking out each value serde rejects and trying
+/// again, so that every type error is reported.
+fn deserialize(text: &mut String, errors: &mut Vec<ConfigError>) -> Option<TomlConfig> {
+    loop {
+        match toml::from_str(text) {
+            Ok(raw) => return Some(raw),
+            Err(e) => {
+                errors.push(toml_error(text, &e));
+                if !e.span().is_some_and(|s| blank_lines(text, s)) {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// Replaces the lines `span` touches with spaces, keeping every offset and
+/// line number as it was. Returns whether anything was left to blank.
+fn blank_lines(text: &mut String, span: Range<usize>) -> bool {
+    let from = span.start.min(text.len());
+    let mut to = span.end.clamp(from, text.len());
+    if to > from && text.as_bytes()[to - 1] == b'\n' {
+        to -= 1;
+    }
+    let start = text[..from].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[to..].find('\n').map_or(text.len(), |i| to + i);
+    let blank: String = text[start..end]
+        .bytes()
+        .map(|b| if b == b'\n' { '\n' } else { ' ' })
+        .collect();
+    let changed = text[start..end].bytes().any(|b| !b.is_ascii_whitespace());
+    text.replace_range(start..end, &blank);
+    changed
+}
+
+/// Line of the key at `path`, for tables that cannot carry their own span.
+fn key_line(root: &Table, path: &[&str], input: &str) -> usize {
+    let mut table: &dyn TableLike = root;
+    let mut span = None;
+    for key in path {
+        span = table.key(key).and_then(|k| k.span());
+        match table.get(key).and_then(|item| item.as_table_like()) {
+            Some(child) => table = child,
+            None => break,
+        }
+    }
+    span.map_or(1, |s| line_of(input, s.start))
+}
+
+/// Converts one table of settings (the top level or the profile at `path`)
+/// to a config, adding any invalid settings to `errors`.
+fn to_config(
+    raw: TomlConfig,
+    path: &[&str],
+    root: &Table,
+    input: &str,
+    errors: &mut Vec<ConfigError>,
+) -> Config {
+    let line = |span: Range<usize>| line_of(input, span.start);
+
+    let mut cfg = Config {
+        reassemble: raw.reassemble,
+        denylist: raw.denylist.map(PathBuf::from),
+        interval_ms: raw.interval_ms,
+        ..Config::default()
+    };
+
+    if let Some(output) = raw.output {
+        cfg.stable_placeholders = output.stable_placeholders;
+        cfg.json_report = output.json_report;
+    }
+
+    if let Some(d) = raw.detectors {
+        let mut detectors = DetectorConfig {
+            email: d.email.and_then(|t| t.enabled),
+            uuid: d.uuid.and_then(|t| t.enabled),
+            jwt: d.jwt.and_then(|t| t.enabled),
+            ..DetectorConfig::default()
+        };
+        if let Some(ip) = d.ip {
+            detectors.ip = ip.enabled;
+            if let Some(names) = ip.classes {
+                let mut classes = IpClasses::none();
+                for name in names {
+                    if !classes.enable(name.get_ref()) {
+                        errors.push(ConfigError::at(
+                            line(name.span()),
+                            format!(
+                                "Unknown IP class '{}' at line {} (expected one of: {})",
+                                name.get_ref(),
+                                line(name.span()),
+                                IpClasses::NAMES.join(", ")
+                            ),
+                        ));
+                    }
+                }
+                detectors.ip_classes = Some(classes);
+            }
+        }
+        if let Some(token) = d.token {
+            detectors.token = token.enabled;
+            detectors.token_min_length = token.min_length;
+            if let Some(t) = token.threshold {
+                match check_score(*t.get_ref(), t.get_ref(), line(t.span())) {
+                    Ok(v) => cfg.token_threshold = Some(v),
+                    Err(e) => errors.push(e),
+                }
+            }
+            for (name, bits) in token.entropy.unwrap_or_default() {
+                let at = line(bits.span());
+                let alphabet = match Alphabet::from_name(&name) {
+                    Some(a) => a,
+                    None => {
+                        let names: Vec<&str> = Alphabet::ALL.iter().map(|a| a.name()).collect();
+                        let key = format!("detectors.token.entropy.{}", name);
+                        errors.push(unknown_key(&key, at, &name, &names));
+                        continue;
+                    }
+                };
+                match check_bits(*bits.get_ref(), bits.get_ref(), alphabet, at) {
+                    Ok(v) => cfg.entropy.push((alphabet, v)),
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+        cfg.detectors = detectors;
+    }
+
+    for (name, section) in raw.rules.unwrap_or_default() {
+        let at_line = |(message, line): (String, usize)| {
+            ConfigError::at(line, format!("{} at line {}", message, line))
+        };
+        let rule_path: Vec<&str> = path.iter().copied().chain(["rules", &name]).collect();
+        let start = key_line(root, &rule_path, input);
+        let mut draft = match RuleDraft::new(&name, start) {
+            Ok(d) => d,
+            Err(m) => {
+                errors.push(at_line((m, start)));
+                continue;
+            }
+        };
+        let mut attrs: Vec<(&str, String, usize)> = Vec::new();
+        if let Some(v) = section.regex {
+            attrs.push(("regex", v.get_ref().clone(), line(v.span())));
+        }
+        if let Some(v) = section.group {
+            attrs.push(("group", v.get_ref().to_string(), line(v.span())));
+        }
+        if let Some(v) = section.entropy {
+            attrs.push(("entropy", v.get_ref().to_string(), line(v.span())));
+        }
+        if let Some(v) = section.label {
+            attrs.push(("label", v.get_ref().clone(), line(v.span())));
+        }
+        if let Some(v) = section.keywords {
+            attrs.push(("keywords", v.get_ref().join(","), line(v.span())));
+        }
+        if let Some(v) = section.severity {
+            attrs.push(("severity", v.get_ref().clone(), line(v.span())));
+        }
+        for (attr, value, at) in attrs {
+            if let Err(m) = draft.set(attr, &value, at) {
+                errors.push(at_line((m, at)));
+            }
+        }
+        match draft.build() {
+            Ok(rule) => cfg.rules.push(rule),
+            Err(e) => errors.push(at_line(e)),
+        }
+    }
+
+    if let Some(allow) = raw.allowlist {
+        for (key, values) in [
+            ("allow", allow.values),
+            ("allow_regex", allow.regexes),
+            ("allow_domain", allow.domains),
+            ("allow_sha256", allow.sha256),
+        ] {
+            for v in values {
+                match parse_allow_entry(key, v.get_ref(), line(v.span())) {
+                    Ok(Some(entry)) => cfg.allowlist.push(entry),
+                    Ok(None) => {}
+                    Err(e) => errors.push(e),
+                }
+            }
+        }
+    }
+
+    if let Some(policy) = raw.policy {
+        let rules = policy.rules.unwrap_or_default();
+        let kinds = [
+            ("email", policy.email),
+            ("ip", policy.ip),
+            ("uuid", policy.uuid),
+            ("jwt", policy.jwt),
+            ("token", policy.token),
+            ("term", policy.term),
+            ("custom", policy.custom),
+        ];
+        let entries = kinds
+            .into_iter()
+            .filter_map(|(kind, v)| v.map(|v| (kind.to_string(), v)))
+            .chain(
+                rules
+                    .into_iter()
+                    .map(|(name, v)| (format!("rule.{}", name), v)),
+            );
+        for (key, v) in entries {
+            match parse_action(v.get_ref(), line(v.span())) {
+                Ok(action) => {
+                    cfg.policy.insert(key, action);
+                }
+                Err(e) => errors.push(e),
+            }
+        }
+        if let Some(v) = policy.on_block {
+            match parse_on_block(v.get_ref(), line(v.span())) {
+                Ok(clear) => cfg.clear_on_block = Some(clear),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    for (kind, v) in raw.severity.unwrap_or_default() {
+        match parse_severity(v.get_ref(), line(v.span())) {
+            Ok(level) => {
+                cfg.severity.insert(kind, level);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+
+    cfg
+}
+
+fn line_of(input: &str, offset: usize) -> usize {
+    input[..offset.min(input.len())].matches('\n').count() + 1
+}
+
+fn unknown_key(key: &str, line: usize, name: &str, known: &[&str]) -> ConfigError {
+    ConfigError::at(
+        line,
+        format!(
+            "Unknown config key '{}' at line {}{}",
+            key,
+            line,
+            did_you_mean(name, known)
+        ),
+    )
+}
+
+/// Turns a TOML/serde error into a line-numbered message, with a suggestion
+/// for misspelled keys.
+fn toml_error(input: &str, e: &toml::de::Error) -> ConfigError {
+    let line = e.span().map_or(1, |s| line_of(input, s.start));
+    let message = e.message();
+    if let Some(rest) = message.strip_prefix("unknown field ") {
+        // serde: "unknown field `x`, expected one of `a`, `b`" / "expected `a`"
+        let names: Vec<&str> = rest.split('`').skip(1).step_by(2).collect();
+        if let Some((unknown, known)) = names.split_first() {
+            return unknown_key(unknown, line, unknown, known);
+        }
+    }
+    ConfigError::at(
+        line,
+        format!("Config parse error at line {}: {}", line, message.trim()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScrubOptions;
+
+    #[test]
+    fn parses_all_sections() {
+        let cfg = parse_toml_config(
+            r#"
+reassemble = true
+
+[output]
+stable_placeholders = true
+
+[detectors.uuid]
+enabled = false
+
+[detectors.ip]
+classes = ["public"]
+
+[detectors.token]
+min_length = 24
+threshold = 0.7
+entropy = { hex = 3.2 }
+
+[rules.aws-key]
+regex = '\b(AKIA[0-9A-Z]{16})\b'
+group = 1
+keywords = ["aws"]
+
+[allowlist]
+values = ["support@acme.io"]
+domains = ["example.com"]
+"#,
+        )
+        .unwrap();
+        let mut opts = ScrubOptions::default();
+        cfg.apply(&mut opts);
+        assert!(opts.stable_placeholders);
+        assert!(opts.reassemble);
+        assert!(!opts.detectors.uuid);
+        assert!(!opts.detectors.ip_classes.private);
+        assert_eq!(opts.detectors.token_min_length, 24);
+        assert_eq!(opts.token_threshold, 0.7);
+        assert_eq!(opts.entropy.hex, 3.2);
+        assert_eq!(opts.rules[0].label, "AWS_KEY");
+        assert!(opts.allowlist.allows("bob@example.com"));
+    }
+
+    #[test]
+    fn suggests_misspelled_keys() {
+        let err = parse_toml_config("[output]\nstable_placehodlers = true\n").unwrap_err();
+        assert_eq!(
+            err[0].message,
+            "Unknown config key 'output.stable_placehodlers' at line 2 (did you mean 'stable_placeholders'?)"
+        );
+        let err = parse_toml_config("\n[detectors.tokens]\nenabled = false\n").unwrap_err();
+        assert_eq!(
+            err[0].message,
+            "Unknown config key 'detectors.tokens' at line 2 (did you mean 'token'?)"
+        );
+    }
+
+    #[test]
+    fn parses_profiles() {
+        let cfg = parse_toml_config(
+            r#"
+[profiles.internal]
+reassemble = true
+
+[profiles.strict]
+extends = "internal"
+[profiles.strict.detectors.token]
+threshold = 0.4
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.profiles["strict"].extends.as_deref(), Some("internal"));
+        let strict = cfg.profile("strict").unwrap();
+        assert_eq!(strict.reassemble, Some(true));
+        assert_eq!(strict.token_threshold, Some(0.4));
+
+        let err = parse_toml_config("extends = \"x\"\n").unwrap_err();
+        assert_eq!(
+            err[0].message,
+            "'extends' is only allowed in a profile at line 1"
+        );
+    }
+
+    #[test]
+    fn reports_every_error() {
+        let err = parse_toml_config(
+            "[output]\njson = true\n[detectors.ip]\nenable = false\n[profiles.a.allowlist]\nvalue = []\n",
+        )
+        .unwrap_err();
+        let lines: Vec<&str> = err.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Unknown config key 'output.json' at line 2",
+                "Unknown config key 'detectors.ip.enable' at line 4 (did you mean 'enabled'?)",
+                "Unknown config key 'profiles.a.allowlist.value' at line 6 (did you mean 'values'?)",
+            ]
+        );
+
+        let err = parse_toml_config(
+            "[rules.a]\nregex = '('\n[rules.b]\nregex = 'x'\ngroup = 1\n[detectors.token]\nthreshold = 3.0\n",
+        )
+        .unwrap_err();
+        assert_eq!(err.len(), 3);
+        assert!(err[2].message.starts_with("Invalid score '3' at line 7"));
+
+        let err = parse_toml_config("[detectors.token]\nentropy = { hex = 4.5 }\n").unwrap_err();
+        assert_eq!(
+            err[0].message,
+            "Invalid entropy '4.5' at line 2 (expected 0.0 to 4.00 bits for hex)"
+        );
+    }
+
+    #[test]
+    fn reports_every_syntax_and_type_error() {
+        let err = parse_toml_config("reassemble = \nx = 1 2\n[output]\njson_report = true\n")
+            .unwrap_err();
+        let lines: Vec<Option<usize>> = err.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![Some(1), Some(2)], "{:?}", err);
+
+        let err = parse_toml_config(
+            "reassemble = \"yes\"\ncolour = 1\n[detectors.token]\nmin_length = \"long\"\nthreshold = 3.0\n[output]\njson_report = 1\n",
+        )
+        .unwrap_err();
+        let lines: Vec<Option<usize>> = err.iter().map(|e| e.line).collect();
+        assert_eq!(
+            lines,
+            vec![Some(1), Some(2), Some(4), Some(5), Some(7)],
+            "{:?}",
+            err
+        );
+        assert!(err[2].message.starts_with("Config parse error at line 4"));
+    }
+
+    #[test]
+    fn reports_rule_errors_with_lines() {
+        let err = parse_toml_config("[rules.x]\nlabel = \"X\"\n").unwrap_err();
+        assert_eq!(err[0].message, "Rule 'x' has no regex at line 1");
+        let err = parse_toml_config("[rules.x]\nregex = 'a'\ngroup = 3\n").unwrap_err();
+        assert!(err[0].message.ends_with("at line 3"), "{}", err[0]);
+    }
+
+    #[test]
+    fn parses_policy_and_severity_sections() {
+        let cfg = parse_toml_config(
+            "[policy]\nemail = \"warn\"\non_block = \"keep\"\n[policy.rules]\npk = \"block\"\n",
+        )
+        .unwrap();
+        assert_eq!(cfg.policy["email"], crate::policy::Action::Warn);
+        assert_eq!(cfg.policy["rule.pk"], crate::policy::Action::Block);
+        assert_eq!(cfg.clear_on_block, Some(false));
+
+        let cfg = parse_toml_config("[severity]\nuuid = \"high\"\n").unwrap();
+        assert_eq!(cfg.severity["uuid"], crate::severity::Severity::High);
+
+        let err = parse_toml_config("[policy]\nip = \"drop\"\n").unwrap_err();
+        assert!(err[0].message.contains("'drop' at line 2"), "{}", err[0]);
+    }
+}
@@ -0,0 +1,143 @@
+use aho_corasick::AhoCorasick;
+
+use crate::config::ConfigError;
+use crate::normalize::normalize;
+
+/// Organization-specific literal terms (customer names, codenames) that have
+/// no pattern but must never be pasted. Matching is whole-word and
+/// case-insensitive, including for non-ASCII letters (`Müller`, `ÅSE`).
+#[derive(Debug, Clone, Default)]
+pub struct Denylist {
+    labels: Vec<Option<String>>,
+    matcher: Option<AhoCorasick>,
+}
+
+impl Denylist {
+    /// Builds a denylist from `(term, label)` pairs. Terms are normalized the
+    /// same way detector input is, so look-alike spellings still match.
+    pub fn new(entries: Vec<(String, Option<String>)>) -> Result<Denylist, ConfigError> {
+        if entries.is_empty() {
+            return Ok(Denylist::default());
+        }
+        let (terms, labels): (Vec<String>, Vec<Option<String>>) = entries
+            .into_iter()
+            .map(|(t, l)| (normalize(&t).text.to_lowercase(), l))
+            .unzip();
+        let matcher = AhoCorasick::builder()
+            .ascii_case_insensitive(true)
+            .build(&terms)
+            .map_err(|e| ConfigError::new(format!("Invalid denylist: {}", e)))?;
+        Ok(Denylist {
+            labels,
+            matcher: Some(matcher),
+        })
+    }
+
+    /// Number of terms.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_none()
+    }
+
+    /// Placeholder label for an entry: its own label or `TERM`.
+    pub fn label(&self, entry: usize) -> &str {
+        self.labels[entry].as_deref().unwrap_or("TERM")
+    }
+
+    /// Non-overlapping whole-word matches in `text` as `(start, end, entry)`,
+    /// preferring the longest term at each position.
+    pub fn find(&self, text: &str) -> Vec<(usize, usize, usize)> {
+        let matcher = match self.matcher.as_ref() {
+            Some(m) => m,
+            None => return Vec::new(),
+        };
+        // Terms are lowercased; ASCII text is matched as is, anything else
+        // through a lowercased copy whose offsets map back to `text`.
+        let folded = (!text.is_ascii()).then(|| fold_case(text));
+        let haystack = folded.as_ref().map_or(text, |(f, _)| f.as_str());
+        let mut hits: Vec<(usize, usize, usize)> = matcher
+            .find_overlapping_iter(haystack)
+            .map(|m| match &folded {
+                Some((_, origin)) => (origin[m.start()], origin[m.end()], m.pattern().as_usize()),
+                None => (m.start(), m.end(), m.pattern().as_usize()),
+            })
+            .filter(|&(start, end, _)| start < end && is_word_boundary(text, start, end))
+            .collect();
+        hits.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+        let mut out: Vec<(usize, usize, usize)> = Vec::new();
+        for hit in hits {
+            if out.last().is_some_and(|&(_, e, _)| hit.0 < e) {
+                continue;
+            }
+            out.push(hit);
+        }
+        out
+    }
+}
+
+/// `text` lowercased, and for each byte of it (plus its end) the offset in
+/// `text` of the character it came from.
+fn fold_case(text: &str) -> (String, Vec<usize>) {
+    let mut folded = String::with_capacity(text.len());
+    let mut origin = Vec::with_capacity(text.len() + 1);
+    for (i, c) in text.char_indices() {
+        for lower in c.to_lowercase() {
+            folded.push(lower);
+            origin.extend(std::iter::repeat_n(i, lower.len_utf8()));
+        }
+    }
+    origin.push(text.len());
+    (folded, origin)
+}
+
+fn is_word_boundary(text: &str, start: usize, end: usize) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    !text[..start].chars().next_back().is_some_and(is_word)
+        && !text[end..].chars().next().is_some_and(is_word)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(terms: &[(&str, Option<&str>)]) -> Denylist {
+        Denylist::new(
+            terms
+                .iter()
+                .map(|(t, l)| (t.to_string(), l.map(|s| s.to_string())))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn matches_whole_words_case_insensitively() {
+        let d = list(&[("Globex", None), ("Project Falcon", Some("CODENAME"))]);
+        let text = "globex ships project falcon; GlobexCorp does not";
+        let hits = d.find(text);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(&text[hits[0].0..hits[0].1], "globex");
+        assert_eq!(d.label(hits[1].2), "CODENAME");
+        assert_eq!(d.label(hits[0].2), "TERM");
+    }
+
+    #[test]
+    fn folds_case_of_non_ascii_terms() {
+        let d = list(&[("Müller", None), ("ÅSE", None)]);
+        let text = "ask MÜLLER or åse, not Müllers";
+        let hits = d.find(text);
+        let found: Vec<&str> = hits.iter().map(|h| &text[h.0..h.1]).collect();
+        assert_eq!(found, vec!["MÜLLER", "åse"]);
+    }
+
+    #[test]
+    fn prefers_longest_term() {
+        let d = list(&[("Acme", None), ("Acme Rockets", None)]);
+        let hits = d.find("ask Acme Rockets");
+        assert_eq!(hits, vec![(4, 16, 1)]);
+    }
+}
@@ -1,6 +1,12 @@
+use std::net::Ipv4Addr;
+
 use once_cell::sync::Lazy;
 use regex::Regex;
 
+use crate::normalize::{normalize_skipping, Normalized};
+use crate::scoring::{benign_regions, is_secret_name, score_token};
+use crate::ScrubOptions;
+
 #[derive(Debug, Default, Clone)]
 pub struct Detections {
     pub emails: Vec<(usize, usize)>,
@@ -8,15 +14,171 @@ pub struct Detections {
     pub uuids: Vec<(usize, usize)>,
     pub jwts: Vec<(usize, usize)>,
     pub tokens: Vec<(usize, usize)>,
+    /// Denylisted terms as `(start, end, entry)`, `entry` indexing the denylist.
+    pub terms: Vec<(usize, usize, usize)>,
+    /// Custom rule matches as `(start, end, rule)`, `rule` indexing the rules.
+    pub custom: Vec<(usize, usize, usize)>,
+    /// Confidence score for each entry in `tokens`, in the same order.
+    pub token_scores: Vec<f64>,
+    /// Line wraps and concatenations that were bridged to reassemble a
+    /// split value (only populated with `ScrubOptions::reassemble`).
+    pub gaps: Vec<(usize, usize)>,
+}
+
+/// Which built-in detectors run, and their tunable parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DetectorOptions {
+    pub email: bool,
+    pub ip: bool,
+    pub uuid: bool,
+    pub jwt: bool,
+    pub token: bool,
+    /// Minimum length of a bare high-entropy run to be a token candidate.
+    pub token_min_length: usize,
+    /// Which kinds of IPv4 address are redacted.
+    pub ip_classes: IpClasses,
+}
+
+impl Default for DetectorOptions {
+    fn default() -> Self {
+        DetectorOptions {
+            email: true,
+            ip: true,
+            uuid: true,
+            jwt: true,
+            token: true,
+            token_min_length: 32,
+            ip_classes: IpClasses::default(),
+        }
+    }
+}
+
+impl DetectorOptions {
+    /// Kinds of the built-in detectors that run.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            (self.email, "email"),
+            (self.ip, "ip"),
+            (self.uuid, "uuid"),
+            (self.jwt, "jwt"),
+            (self.token, "token"),
+        ]
+        .iter()
+        .filter(|(on, _)| *on)
+        .map(|&(_, kind)| kind)
+        .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpClasses {
+    pub public: bool,
+    /// 10/8, 172.16/12 and 192.168/16.
+    pub private: bool,
+    /// 127/8.
+    pub loopback: bool,
+    /// 169.254/16.
+    pub link_local: bool,
+    /// Unspecified, broadcast, multicast and documentation ranges.
+    pub special: bool,
+}
+
+impl Default for IpClasses {
+    fn default() -> Self {
+        IpClasses {
+            public: true,
+            private: true,
+            loopback: true,
+            link_local: true,
+            special: true,
+        }
+    }
+}
+
+impl IpClasses {
+    pub const NAMES: [&'static str; 5] = ["public", "private", "loopback", "link_local", "special"];
+
+    pub fn none() -> IpClasses {
+        IpClasses {
+            public: false,
+            private: false,
+            loopback: false,
+            link_local: false,
+            special: false,
+        }
+    }
+
+    /// Enables a class by name; returns false for an unknown name.
+    pub fn enable(&mut self, name: &str) -> bool {
+        let slot = match name {
+            "public" => &mut self.public,
+            "private" => &mut self.private,
+            "loopback" => &mut self.loopback,
+            "link_local" => &mut self.link_local,
+            "special" => &mut self.special,
+            _ => return false,
+        };
+        *slot = true;
+        true
+    }
+
+    /// Names of the enabled classes, in [`IpClasses::NAMES`] order.
+    pub fn names(&self) -> Vec<&'static str> {
+        let enabled = [
+            self.public,
+            self.private,
+            self.loopback,
+            self.link_local,
+            self.special,
+        ];
+        IpClasses::NAMES
+            .into_iter()
+            .zip(enabled)
+            .filter_map(|(name, on)| on.then_some(name))
+            .collect()
+    }
+
+    pub fn allows(&self, ip: Ipv4Addr) -> bool {
+        if ip.is_loopback() {
+            self.loopback
+        } else if ip.is_private() {
+            self.private
+        } else if ip.is_link_local() {
+            self.link_local
+        } else if ip.is_unspecified()
+            || ip.is_broadcast()
+            || ip.is_multicast()
+            || ip.is_documentation()
+        {
+            self.special
+        } else {
+            self.public
+        }
+    }
 }
 
 static EMAIL_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap());
 
 static IPV4_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b").unwrap()
+    Regex::new(r"\b(?:(?:25[0-5]|2[0-4]\d|[01]?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|[01]?\d?\d)\b")
+        .unwrap()
 });
 
+/// Parses a dotted quad, accepting leading zeros (`192.168.001.010`) that
+/// `Ipv4Addr::from_str` rejects.
+fn parse_ipv4(s: &str) -> Option<Ipv4Addr> {
+    let mut octets = [0u8; 4];
+    let mut parts = s.split('.');
+    for octet in octets.iter_mut() {
+        *octet = parts.next()?.parse().ok()?;
+    }
+    match parts.next() {
+        Some(_) => None,
+        None => Some(Ipv4Addr::from(octets)),
+    }
+}
+
 static UUID_V4_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(
         r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-4[0-9a-fA-F]{3}-[89abAB][0-9a-fA-F]{3}-[0-9a-fA-F]{12}\b",
@@ -27,35 +189,276 @@ static UUID_V4_RE: Lazy<Regex> = Lazy::new(|| {
 static JWT_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\b[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\b").unwrap());
 
-static TOKEN_CANDIDATE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\b[A-Za-z0-9_-]{32,}\b").unwrap());
+static TOKEN_CANDIDATE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b[A-Za-z0-9_-]+\b").unwrap());
 
+/// Runs of the standard base64 alphabet, which [`TOKEN_CANDIDATE_RE`] splits
+/// at every `+` and `/`.
+static BASE64_CANDIDATE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"[A-Za-z0-9+/]+={0,2}").unwrap());
+
+static CONTINUATION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"\\\r?\n[ \t]*|["'][ \t]*\+[ \t]*(?:\r?\n[ \t]*)?["']|\r?\n[ \t>]*"#).unwrap()
+});
+
+/// `name = value` and `name: value` pairs; the name must pass
+/// [`is_secret_name`] for the value to be a candidate.
+static SECRET_ASSIGNMENT_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([\w.-]+)["']?[ \t]*[:=][ \t]*["']?([^\s"',;]{8,})"#).unwrap());
+
+type Span = (usize, usize);
+
+// How far back on the same line to look for keywords like `secret`.
+const CONTEXT_BYTES: usize = 40;
+
+// A bare newline only counts as a soft wrap after a run this long.
+const MIN_WRAPPED_FRAGMENT: usize = 16;
+
+/// Runs all detectors over a normalized view of `input`. Returned spans are
+/// byte ranges in the original `input`, so they cover any zero-width or
+/// look-alike characters the match was built from.
 pub fn detect(input: &str) -> Detections {
-    let mut det = Detections::default();
+    detect_with_options(input, &ScrubOptions::default())
+}
 
-    for m in EMAIL_RE.find_iter(input) {
-        det.emails.push((m.start(), m.end()));
+pub fn detect_with_options(input: &str, options: &ScrubOptions) -> Detections {
+    let (gaps, wraps) = if options.reassemble {
+        continuation_gaps(input)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+    let norm = normalize_skipping(input, &gaps);
+    let text = norm.text.as_str();
+    let mut det = Detections {
+        gaps,
+        ..Detections::default()
+    };
+
+    let enabled = &options.detectors;
+
+    if enabled.email {
+        for m in EMAIL_RE.find_iter(text) {
+            det.emails.push(norm.to_original(m.start(), m.end()));
+        }
+    }
+    if enabled.ip {
+        for m in IPV4_RE.find_iter(text) {
+            // An address that cannot be classified is still an address.
+            let class_ok = parse_ipv4(m.as_str()).is_none_or(|ip| enabled.ip_classes.allows(ip));
+            if class_ok {
+                det.ips.push(norm.to_original(m.start(), m.end()));
+            }
+        }
+    }
+    if enabled.uuid {
+        for m in UUID_V4_RE.find_iter(text) {
+            det.uuids.push(norm.to_original(m.start(), m.end()));
+        }
+    }
+    if enabled.jwt {
+        for m in JWT_RE.find_iter(text) {
+            det.jwts.push(norm.to_original(m.start(), m.end()));
+        }
     }
-    for m in IPV4_RE.find_iter(input) {
-        det.ips.push((m.start(), m.end()));
+
+    for (idx, rule) in options.rules.iter().enumerate() {
+        for (start, end) in rule.find(text, |at| keyword_context(text, at)) {
+            let (start, end) = norm.to_original(start, end);
+            det.custom.push((start, end, idx));
+        }
     }
-    for m in UUID_V4_RE.find_iter(input) {
-        det.uuids.push((m.start(), m.end()));
+
+    for (start, end, entry) in options.denylist.find(text) {
+        let (start, end) = norm.to_original(start, end);
+        det.terms.push((start, end, entry));
     }
-    for m in JWT_RE.find_iter(input) {
-        det.jwts.push((m.start(), m.end()));
+
+    if !enabled.token {
+        return det;
     }
 
-    for m in TOKEN_CANDIDATE_RE.find_iter(input) {
-        let s = &input[m.start()..m.end()];
-        if shannon_entropy(s) >= 3.5 {
-            det.tokens.push((m.start(), m.end()));
+    let benign = benign_regions(text);
+    for (start, end) in token_candidates(text, enabled.token_min_length) {
+        if let Some(score) = token_score(text, &benign, start, end, options) {
+            push_token(&mut det, &norm, start, end, score);
         }
     }
+    if !wraps.is_empty() {
+        join_wrapped_tokens(input, &wraps, options, &mut det);
+    }
 
     det
 }
 
+/// Bare runs of token characters or of standard base64 at least
+/// `min_length` long, plus the values of `secret = ...` style assignments,
+/// sorted by start.
+fn token_candidates(text: &str, min_length: usize) -> Vec<(usize, usize)> {
+    let mut candidates: Vec<(usize, usize)> = TOKEN_CANDIDATE_RE
+        .find_iter(text)
+        .filter(|m| m.len() >= min_length)
+        .map(|m| (m.start(), m.end()))
+        .collect();
+    for m in BASE64_CANDIDATE_RE.find_iter(text) {
+        if m.len() >= min_length && is_bare_base64(text, m.start(), m.end()) {
+            // The run replaces the pieces it was split into above.
+            candidates.retain(|&(s, e)| e <= m.start() || m.end() <= s);
+            candidates.push((m.start(), m.end()));
+        }
+    }
+    for caps in SECRET_ASSIGNMENT_RE.captures_iter(text) {
+        if !is_secret_name(&caps[1]) {
+            continue;
+        }
+        let v = caps.get(2).unwrap();
+        if !candidates
+            .iter()
+            .any(|&(s, e)| v.start() < e && s < v.end())
+        {
+            candidates.push((v.start(), v.end()));
+        }
+    }
+    candidates.sort();
+    candidates
+}
+
+/// Whether `text[start..end]` is a base64 value on its own: it uses `+` or
+/// `/`, mixes upper and lower case with digits, and is not a path of words
+/// or part of a URL, email address or longer identifier.
+fn is_bare_base64(text: &str, start: usize, end: usize) -> bool {
+    let value = &text[start..end];
+    let before = text[..start].chars().next_back();
+    let after = text[end..].chars().next();
+    // `api/v2/Users/Profile1`: each part letters, then maybe digits.
+    let is_path = !value.contains('+')
+        && value.split('/').all(|part| {
+            let digits = part.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+            digits.len() < part.len() && digits.bytes().all(|b| b.is_ascii_digit())
+        });
+    value.contains(['+', '/'])
+        && !is_path
+        && !value.starts_with('/')
+        && !value.trim_end_matches('=').ends_with('/')
+        && value.contains(|c: char| c.is_ascii_uppercase())
+        && value.contains(|c: char| c.is_ascii_lowercase())
+        && value.contains(|c: char| c.is_ascii_digit())
+        && !before.is_some_and(|c| matches!(c, '.' | ':' | '@' | '~' | '_' | '-' | '%' | '\\'))
+        && !after.is_some_and(|c| matches!(c, '.' | '@' | '_' | '-' | '%'))
+}
+
+/// The confidence score of `text[start..end]` if it is a token.
+fn token_score(
+    text: &str,
+    benign: &[(usize, usize)],
+    start: usize,
+    end: usize,
+    options: &ScrubOptions,
+) -> Option<f64> {
+    if !options.entropy.passes(&text[start..end]) {
+        return None;
+    }
+    let in_benign = benign.iter().any(|&(s, e)| s <= start && end <= e);
+    let score = score_token(&text[start..end], keyword_context(text, start), in_benign);
+    (score >= options.token_threshold).then_some(score)
+}
+
+/// Soft wraps are only bridged for tokens: a candidate that runs across one
+/// replaces the tokens found in its fragments if the joined value passes the
+/// token test on its own. Other detectors never see the lines joined.
+fn join_wrapped_tokens(
+    input: &str,
+    wraps: &[(usize, usize)],
+    options: &ScrubOptions,
+    det: &mut Detections,
+) {
+    let mut skip: Vec<(usize, usize)> = det.gaps.iter().chain(wraps).copied().collect();
+    skip.sort();
+    let joined = normalize_skipping(input, &skip);
+    let text = joined.text.as_str();
+    let benign = benign_regions(text);
+    for (start, end) in token_candidates(text, options.detectors.token_min_length) {
+        let (orig_start, orig_end) = joined.to_original(start, end);
+        let bridged: Vec<(usize, usize)> = wraps
+            .iter()
+            .filter(|&&(s, e)| orig_start < s && e < orig_end)
+            .copied()
+            .collect();
+        if bridged.is_empty() {
+            continue;
+        }
+        let Some(score) = token_score(text, &benign, start, end, options) else {
+            continue;
+        };
+        let mut i = 0;
+        while i < det.tokens.len() {
+            let (s, e) = det.tokens[i];
+            if s < orig_end && orig_start < e {
+                det.tokens.remove(i);
+                det.token_scores.remove(i);
+            } else {
+                i += 1;
+            }
+        }
+        push_token(det, &joined, start, end, score);
+        det.gaps.extend(bridged);
+    }
+    det.gaps.sort();
+}
+
+fn push_token(det: &mut Detections, norm: &Normalized, start: usize, end: usize, score: f64) {
+    det.tokens.push(norm.to_original(start, end));
+    det.token_scores.push(score);
+}
+
+/// The key or phrase directly in front of `start`: same line, and cut after
+/// any earlier quoted value or separator so one `key=` doesn't taint the rest.
+fn keyword_context(text: &str, start: usize) -> &str {
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let mut from = start.saturating_sub(CONTEXT_BYTES).max(line_start);
+    while !text.is_char_boundary(from) {
+        from += 1;
+    }
+    let ctx = text[from..start]
+        .trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | ':' | '='));
+    match ctx.rfind(['"', '\'', ',', ';', '&']) {
+        Some(i) => &ctx[i + 1..],
+        None => ctx,
+    }
+}
+
+/// Finds separators that split a single value: `\\` line continuations and
+/// `"abc" + "def"` concatenations, then, separately, soft wraps inside long
+/// runs, which only token candidates may be joined across.
+fn continuation_gaps(input: &str) -> (Vec<Span>, Vec<Span>) {
+    let (mut gaps, mut wraps) = (Vec::new(), Vec::new());
+    for m in CONTINUATION_RE.find_iter(input) {
+        let before = &input[..m.start()];
+        let after = &input[m.end()..];
+        if !before.chars().next_back().is_some_and(is_token_char)
+            || !after.chars().next().is_some_and(is_token_char)
+        {
+            continue;
+        }
+        let bare_newline = matches!(m.as_str().as_bytes()[0], b'\r' | b'\n');
+        if bare_newline {
+            let run = before
+                .chars()
+                .rev()
+                .take_while(|&c| is_token_char(c))
+                .count();
+            if run >= MIN_WRAPPED_FRAGMENT {
+                wraps.push((m.start(), m.end()));
+            }
+            continue;
+        }
+        gaps.push((m.start(), m.end()));
+    }
+    (gaps, wraps)
+}
+
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
 pub fn shannon_entropy(s: &str) -> f64 {
     if s.is_empty() {
         return 0.0;
@@ -92,6 +495,24 @@ mod tests {
         assert_eq!(d.ips.len(), 1);
     }
 
+    #[test]
+    fn detects_ipv4_with_leading_zeros() {
+        let (out, _, _) =
+            crate::scrub_text_with_findings("host 192.168.001.010", &ScrubOptions::default());
+        assert_eq!(out, "host <IP>");
+
+        let mut opts = ScrubOptions::default();
+        opts.detectors.ip_classes = IpClasses::none();
+        opts.detectors.ip_classes.enable("public");
+        assert!(detect_with_options("host 192.168.001.010", &opts)
+            .ips
+            .is_empty());
+        assert_eq!(
+            parse_ipv4("010.000.000.001"),
+            Some(Ipv4Addr::new(10, 0, 0, 1))
+        );
+    }
+
     #[test]
     fn detects_uuid_v4() {
         let d = detect("id 123e4567-e89b-42d3-a456-556642440000");
@@ -111,6 +532,122 @@ mod tests {
         assert_eq!(d.tokens.len(), 1);
     }
 
+    #[test]
+    fn detects_bare_base64_token_whole() {
+        let text = "here: 9Vx2kQ+7bL/pZ3rT8mW1nY4cJ6hA0sD5fG== ok";
+        assert_eq!(detect(text).tokens, vec![(6, 42)]);
+
+        for benign in [
+            "see api/v2/Users/Profile1/Settings/Advanced",
+            "https://github.com/Owner2/SomeRepo/blob/Main3/README.md",
+            "\"integrity\": \"sha512-Kq5sNclPz7QV2+lfQIuu3sABy6M3Q8fTqQl5F2w/SkHX8Q7dTPnrCkKBZ9tE5bfS+A==\"",
+            "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNkYPhfDwAChwGA60e6kgAAAABJRU5ErkJggg==",
+        ] {
+            assert!(detect(benign).tokens.is_empty(), "{}", benign);
+        }
+    }
+
+    #[test]
+    fn detects_obfuscated_email_on_original_bytes() {
+        let input = "mail аdmin\u{200B}＠example.com now";
+        let d = detect(input);
+        assert_eq!(d.emails.len(), 1);
+        let (s, e) = d.emails[0];
+        assert_eq!(&input[s..e], "аdmin\u{200B}＠example.com");
+    }
+
+    #[test]
+    fn reassembles_wrapped_and_concatenated_tokens() {
+        let opts = ScrubOptions {
+            reassemble: true,
+            ..ScrubOptions::default()
+        };
+        let wrapped = "key AbCDeF0123456789Ab\nCDeF0123456789 end";
+        assert_eq!(detect(wrapped).tokens.len(), 0);
+        let d = detect_with_options(wrapped, &opts);
+        assert_eq!(d.tokens, vec![(4, wrapped.len() - 4)]);
+
+        let concat = r#"k = "AbCDeF0123456789" + "AbCDeF0123456789";"#;
+        let d = detect_with_options(concat, &opts);
+        assert_eq!(d.tokens.len(), 1);
+        assert_eq!(d.gaps.len(), 1);
+    }
+
+    #[test]
+    fn soft_wraps_only_join_tokens() {
+        let opts = ScrubOptions {
+            reassemble: true,
+            ..ScrubOptions::default()
+        };
+        let input = "some_identifier_long_name\nbob@example.com\n";
+        let d = detect_with_options(input, &opts);
+        assert_eq!(d.emails, vec![(26, 41)]);
+        assert!(d.tokens.is_empty());
+        assert!(d.gaps.is_empty());
+        let (out, _, _) = crate::scrub_text_with_findings(input, &opts);
+        assert_eq!(out, "some_identifier_long_name\n<EMAIL>\n");
+    }
+
+    #[test]
+    fn short_lines_are_not_joined() {
+        let opts = ScrubOptions {
+            reassemble: true,
+            ..ScrubOptions::default()
+        };
+        let d = detect_with_options("hello\nworld", &opts);
+        assert!(d.gaps.is_empty());
+    }
+
+    #[test]
+    fn keywords_inside_words_do_not_make_tokens() {
+        for input in [
+            "monkey=bananaSplit99",
+            "author: JohnSmith2024x",
+            "keyboard_layout=QwertyLayout1",
+            "sortKey=createdAt2024x",
+        ] {
+            let (out, _, _) = crate::scrub_text_with_findings(input, &ScrubOptions::default());
+            assert_eq!(out, input);
+        }
+        let (out, _, _) =
+            crate::scrub_text_with_findings("apiKey=bananaSplit99", &ScrubOptions::default());
+        assert_eq!(out, "apiKey=<TOKEN>");
+    }
+
+    #[test]
+    fn skips_git_sha_but_catches_keyword_password() {
+        let d = detect("commit e83c5163316f89bfbd7d9ab23ca2e25604af290a");
+        assert!(d.tokens.is_empty());
+        let d = detect("db_password = \"Hunter2!x\"");
+        assert_eq!(d.tokens.len(), 1);
+        assert_eq!(d.token_scores.len(), 1);
+        let d = detect("api_key: \"Hunter2!xyz\" commit e83c5163316f89bfbd7d9ab23ca2e25604af290a");
+        assert_eq!(d.tokens.len(), 1);
+    }
+
+    #[test]
+    fn respects_disabled_detectors_and_ip_classes() {
+        let mut opts = ScrubOptions::default();
+        opts.detectors.uuid = false;
+        opts.detectors.ip_classes = IpClasses::none();
+        opts.detectors.ip_classes.enable("public");
+        let d = detect_with_options(
+            "id 123e4567-e89b-42d3-a456-556642440000 from 10.0.0.1 and 8.8.8.8",
+            &opts,
+        );
+        assert!(d.uuids.is_empty());
+        assert_eq!(d.ips.len(), 1);
+    }
+
+    #[test]
+    fn token_min_length_is_tunable() {
+        let input = "tok Xy7Qa9Lm2Rt5Vb8N";
+        assert!(detect(input).tokens.is_empty());
+        let mut opts = ScrubOptions::default();
+        opts.detectors.token_min_length = 16;
+        assert_eq!(detect_with_options(input, &opts).tokens.len(), 1);
+    }
+
     #[test]
     fn entropy_low_for_repetitive() {
         let s = "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
@@ -0,0 +1,228 @@
+//! Added lines of a unified diff (`git diff` output), so only new content is
+//! checked.
+
+/// The lines a diff adds to one file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddedLines {
+    /// Path of the file after the change.
+    pub path: String,
+    /// `(line number in the new file, text)` for every added line.
+    pub lines: Vec<(usize, String)>,
+}
+
+impl AddedLines {
+    /// The added lines joined with `\n`, and the new-file line number of each
+    /// line of that text.
+    pub fn text(&self) -> (String, Vec<usize>) {
+        let text = self
+            .lines
+            .iter()
+            .map(|(_, l)| l.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (text, self.lines.iter().map(|&(n, _)| n).collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct DiffError {
+    pub message: String,
+}
+
+impl std::fmt::Display for DiffError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Parses a unified diff into the lines it adds, per file. Deleted files and
+/// binary changes add nothing and are left out.
+pub fn parse_unified_diff(diff: &str) -> Result<Vec<AddedLines>, DiffError> {
+    let mut files: Vec<AddedLines> = Vec::new();
+    let mut current: Option<AddedLines> = None;
+    let mut hunk: Option<Hunk> = None;
+    for (i, line) in diff.lines().enumerate() {
+        if let Some(h) = hunk.as_mut().filter(|h| h.old_left > 0 || h.new_left > 0) {
+            match line.as_bytes().first() {
+                Some(b'+') => {
+                    if let Some(file) = current.as_mut() {
+                        file.lines.push((h.next, line[1..].to_string()));
+                    }
+                    h.next += 1;
+                    h.new_left = h.new_left.saturating_sub(1);
+                }
+                Some(b'-') => h.old_left = h.old_left.saturating_sub(1),
+                // "\ No newline at end of file"
+                Some(b'\\') => {}
+                _ => {
+                    h.next += 1;
+                    h.old_left = h.old_left.saturating_sub(1);
+                    h.new_left = h.new_left.saturating_sub(1);
+                }
+            }
+            continue;
+        }
+        if line.starts_with("diff ") {
+            files.extend(current.take());
+            hunk = None;
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            files.extend(current.take());
+            current = new_path(path).map(|path| AddedLines {
+                path,
+                lines: Vec::new(),
+            });
+        } else if line.starts_with("@@") {
+            hunk = Some(Hunk::parse(line).ok_or_else(|| DiffError {
+                message: format!("Invalid hunk header at line {} of the diff", i + 1),
+            })?);
+        }
+    }
+    files.extend(current);
+    files.retain(|f| !f.lines.is_empty());
+    Ok(files)
+}
+
+/// Position inside a hunk: the next new-file line number and how many old
+/// and new lines are still to come.
+struct Hunk {
+    next: usize,
+    old_left: usize,
+    new_left: usize,
+}
+
+impl Hunk {
+    /// Parses `@@ -a,b +c,d @@`; a missing count means 1.
+    fn parse(header: &str) -> Option<Hunk> {
+        let mut ranges = header.split_whitespace().skip(1);
+        let old = ranges.next()?.strip_prefix('-')?;
+        let new = ranges.next()?.strip_prefix('+')?;
+        let count = |range: &str| -> Option<(usize, usize)> {
+            let (start, len) = range.split_once(',').unwrap_or((range, "1"));
+            Some((start.parse().ok()?, len.parse().ok()?))
+        };
+        let (_, old_left) = count(old)?;
+        let (next, new_left) = count(new)?;
+        Some(Hunk {
+            next,
+            old_left,
+            new_left,
+        })
+    }
+}
+
+/// The path from a `+++ b/path` header; `None` for a deleted file.
+fn new_path(header: &str) -> Option<String> {
+    let header = header.split('\t').next().unwrap_or(header).trim_end();
+    if header == "/dev/null" {
+        return None;
+    }
+    let path = match header.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+        Some(quoted) => unquote(quoted),
+        None => header.to_string(),
+    };
+    match path.strip_prefix("b/") {
+        Some(p) => Some(p.to_string()),
+        None => Some(path),
+    }
+}
+
+/// Undoes git's C-style quoting of unusual paths: `\t`, `\"`, `\\` and
+/// friends, and `\303\251` octal escapes for the bytes of non-ASCII names.
+fn unquote(quoted: &str) -> String {
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut rest = quoted.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let Some((&e, tail)) = rest.split_first() else {
+            bytes.push(b);
+            break;
+        };
+        rest = tail;
+        let octal = |c: u8| (b'0'..=b'7').contains(&c);
+        bytes.push(match e {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'v' => 0x0b,
+            b'f' => 0x0c,
+            b'r' => b'\r',
+            b'0'..=b'3' if rest.len() >= 2 && octal(rest[0]) && octal(rest[1]) => {
+                let n = (e - b'0') * 64 + (rest[0] - b'0') * 8 + (rest[1] - b'0');
+                rest = &rest[2..];
+                n
+            }
+            other => other,
+        });
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/app.env b/app.env
+index 1111111..2222222 100644
+--- a/app.env
++++ b/app.env
+@@ -1,3 +1,4 @@
+ NAME=demo
+-TOKEN=old
++TOKEN=new
++MAIL=a@b.io
+ PORT=80
+@@ -10,0 +12,2 @@ x
++LAST=1
++++ not a header
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+diff --git a/logo.png b/logo.png
+Binary files a/logo.png and b/logo.png differ
+";
+
+    #[test]
+    fn keeps_added_lines_with_new_line_numbers() {
+        let files = parse_unified_diff(DIFF).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, "app.env");
+        assert_eq!(
+            files[0].lines,
+            vec![
+                (2, "TOKEN=new".to_string()),
+                (3, "MAIL=a@b.io".to_string()),
+                (12, "LAST=1".to_string()),
+                (13, "++ not a header".to_string()),
+            ]
+        );
+        let (text, numbers) = files[0].text();
+        assert!(text.starts_with("TOKEN=new\nMAIL=a@b.io\nLAST=1\n"));
+        assert_eq!(numbers, vec![2, 3, 12, 13]);
+    }
+
+    #[test]
+    fn decodes_quoted_paths() {
+        let diff = "+++ \"b/caf\\303\\251 \\\"x\\\".txt\"\n@@ -0,0 +1 @@\n+hi\n";
+        let files = parse_unified_diff(diff).unwrap();
+        assert_eq!(files[0].path, "café \"x\".txt");
+        assert_eq!(
+            new_path("b/plain name.txt\t").as_deref(),
+            Some("plain name.txt")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_hunks() {
+        let err = parse_unified_diff("+++ b/x\n@@ nonsense @@\n").unwrap_err();
+        assert_eq!(err.message, "Invalid hunk header at line 2 of the diff");
+    }
+}
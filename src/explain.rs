@@ -0,0 +1,273 @@
+//! Why each detected value was or was not redacted, for `scrubby explain`.
+
+use crate::allowlist::Allowlist;
+use crate::detectors::shannon_entropy;
+use crate::location::LineIndex;
+use crate::policy::{Action, Policy};
+use crate::scoring::Alphabet;
+use crate::severity::Severity;
+use crate::{scrub_text_with_findings, ScrubOptions};
+
+/// What happened to a detected value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// Replaced by this placeholder.
+    Redacted(String),
+    /// Left in place by a `warn` policy.
+    Warned,
+    /// Matched a `block` policy; redacted with this placeholder, but nothing
+    /// is written.
+    Blocked(String),
+    /// Left in place because this allowlist entry matched.
+    Allowlisted(String),
+    /// Left in place by an `allow` policy.
+    Allowed,
+    /// Left in place by a choice made during `--review`.
+    Kept,
+}
+
+impl Outcome {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Outcome::Redacted(_) => "redacted",
+            Outcome::Warned => "warned",
+            Outcome::Blocked(_) => "blocked",
+            Outcome::Allowlisted(_) => "allowlisted",
+            Outcome::Allowed => "allowed",
+            Outcome::Kept => "kept",
+        }
+    }
+
+    /// The placeholder that replaced the value, if it was replaced.
+    pub fn placeholder(&self) -> Option<&str> {
+        match self {
+            Outcome::Redacted(p) | Outcome::Blocked(p) => Some(p),
+            _ => None,
+        }
+    }
+}
+
+/// One detected value and how scrubbing treated it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    /// The detector: `email`, `ip`, `uuid`, `jwt`, `token`, `term` or
+    /// `custom`.
+    pub kind: &'static str,
+    /// Name of the custom rule that matched.
+    pub rule: Option<String>,
+    /// Which pattern matched: `builtin.<kind>`, `rule.<name>` or `denylist`.
+    pub pattern_id: String,
+    /// The regex of a custom rule.
+    pub pattern: Option<String>,
+    /// Byte offsets of the value in the input.
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub value: String,
+    pub severity: Severity,
+    /// Shannon entropy of the value in bits per character.
+    pub entropy: f64,
+    /// The narrowest alphabet the value is drawn from.
+    pub alphabet: &'static str,
+    /// Entropy the value had to reach: the alphabet's floor for tokens, a
+    /// rule's `entropy` setting for custom findings.
+    pub entropy_floor: Option<f64>,
+    /// Confidence score of a heuristically detected token.
+    pub score: Option<f64>,
+    /// Score a token had to reach.
+    pub threshold: Option<f64>,
+    /// The policy entry that applied, e.g. `jwt = block`.
+    pub policy: String,
+    /// Allowlist entries compared with the value, as `key=value` and in
+    /// order, up to the first that matched.
+    pub consulted: Vec<String>,
+    pub outcome: Outcome,
+}
+
+/// Explains every value the detectors find in `input` under `options`,
+/// including the ones the allowlist or a policy left in place.
+pub fn explain(input: &str, options: &ScrubOptions) -> Vec<Explanation> {
+    // Without the allowlist and policies, every detected value is a finding.
+    let probe = ScrubOptions {
+        allowlist: Allowlist::default(),
+        policy: Policy::default(),
+        kept: Vec::new(),
+        ..options.clone()
+    };
+    let (_, _, detected) = scrub_text_with_findings(input, &probe);
+    let (_, _, findings) = scrub_text_with_findings(input, options);
+    let index = LineIndex::new(input);
+
+    detected
+        .into_iter()
+        .map(|d| {
+            let value = &input[d.start..d.end];
+            let (line, column) = index.locate(d.start);
+            let (action, key) = options.policy.action(d.kind, d.rule.as_deref());
+            let kept = options.kept.contains(&(d.start, d.end));
+            let mut consulted = Vec::new();
+            let mut matched = None;
+            for entry in options.allowlist.entries().iter().filter(|_| !kept) {
+                let (k, v) = entry.to_config();
+                consulted.push(format!("{}={}", k, v));
+                if entry.matches(value) {
+                    matched = consulted.last().cloned();
+                    break;
+                }
+            }
+            let found = findings
+                .iter()
+                .find(|f| (f.start, f.end) == (d.start, d.end));
+            let outcome = match (found, matched) {
+                (Some(f), _) if f.action == Action::Warn => Outcome::Warned,
+                (Some(f), _) if f.action == Action::Block => {
+                    Outcome::Blocked(f.placeholder.clone())
+                }
+                (Some(f), _) => Outcome::Redacted(f.placeholder.clone()),
+                (None, _) if kept => Outcome::Kept,
+                (None, Some(entry)) => Outcome::Allowlisted(entry),
+                (None, None) => Outcome::Allowed,
+            };
+            let rule = d
+                .rule
+                .as_ref()
+                .and_then(|name| options.rules.iter().find(|r| r.name == *name));
+            let alphabet = Alphabet::of(value);
+            let entropy_floor = match d.kind {
+                "token" => Some(options.entropy.get(alphabet)),
+                _ => rule.and_then(|r| r.min_entropy),
+            };
+            Explanation {
+                kind: d.kind,
+                pattern_id: d.pattern_id(),
+                pattern: rule.map(|r| r.regex.as_str().to_string()),
+                rule: d.rule,
+                start: d.start,
+                end: d.end,
+                line,
+                column,
+                value: value.to_string(),
+                severity: d.severity,
+                entropy: shannon_entropy(value),
+                alphabet: alphabet.name(),
+                entropy_floor,
+                threshold: d.score.map(|_| options.token_threshold),
+                score: d.score,
+                policy: format!("{} = {}", key, action.name()),
+                consulted,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+/// A multi-line description of `e`, headed `name:line:column:`.
+pub fn format_explanation(name: &str, e: &Explanation) -> String {
+    let mut lines = vec![format!(
+        "{}:{}:{}: {} {:?}",
+        name, e.line, e.column, e.kind, e.value
+    )];
+    lines.push(format!("  detector:  {} ({})", e.kind, e.pattern_id));
+    if let Some(pattern) = &e.pattern {
+        lines.push(format!("  pattern:   {}", pattern));
+    }
+    lines.push(format!("  span:      bytes {}..{}", e.start, e.end));
+    lines.push(format!("  severity:  {}", e.severity));
+    let floor = match e.entropy_floor {
+        Some(f) => format!(", floor {:.2}", f),
+        None => String::new(),
+    };
+    lines.push(format!(
+        "  entropy:   {:.2} bits/char ({}{})",
+        e.entropy, e.alphabet, floor
+    ));
+    if let (Some(score), Some(threshold)) = (e.score, e.threshold) {
+        lines.push(format!(
+            "  score:     {:.2} (threshold {:.2})",
+            score, threshold
+        ));
+    }
+    lines.push(format!("  policy:    {}", e.policy));
+    let allowlist = match (&e.outcome, e.consulted.len()) {
+        (Outcome::Allowlisted(entry), n) => format!("matched {} ({} consulted)", entry, n),
+        (Outcome::Kept, _) => "not consulted".to_string(),
+        (_, 0) => "empty".to_string(),
+        (_, 1) => "1 entry consulted, none matched".to_string(),
+        (_, n) => format!("{} entries consulted, none matched", n),
+    };
+    lines.push(format!("  allowlist: {}", allowlist));
+    let outcome = match &e.outcome {
+        Outcome::Redacted(p) => format!("redacted as {}", p),
+        Outcome::Warned => "left in place (warn policy)".to_string(),
+        Outcome::Blocked(p) => format!("blocked (redacted as {})", p),
+        Outcome::Allowlisted(_) => "left in place (allowlisted)".to_string(),
+        Outcome::Allowed => "left in place (allow policy)".to_string(),
+        Outcome::Kept => "left in place (kept during review)".to_string(),
+    };
+    lines.push(format!("  outcome:   {}", outcome));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::allowlist::AllowEntry;
+    use crate::rules::RuleDraft;
+
+    #[test]
+    fn explains_redacted_and_allowlisted_values() {
+        let mut options = ScrubOptions::default();
+        options
+            .allowlist
+            .push(AllowEntry::from_config("allow", "x@y.io").unwrap().unwrap());
+        options.allowlist.push(
+            AllowEntry::from_config("allow_domain", "acme.io")
+                .unwrap()
+                .unwrap(),
+        );
+        options.policy.set("ip", Action::Warn);
+        let text = "ops@acme.io\nmail a@b.io from 10.0.0.1";
+        let found = explain(text, &options);
+        let outcomes: Vec<&str> = found.iter().map(|e| e.outcome.name()).collect();
+        assert_eq!(outcomes, vec!["allowlisted", "redacted", "warned"]);
+
+        assert_eq!(
+            found[0].outcome,
+            Outcome::Allowlisted("allow_domain=acme.io".to_string())
+        );
+        assert_eq!(found[0].consulted.len(), 2);
+        assert_eq!((found[1].line, found[1].column), (2, 6));
+        assert_eq!(found[2].policy, "ip = warn");
+        assert_eq!(
+            format_explanation("notes", &found[1]),
+            "notes:2:6: email \"a@b.io\"\n\
+             \x20 detector:  email (builtin.email)\n\
+             \x20 span:      bytes 17..23\n\
+             \x20 severity:  medium\n\
+             \x20 entropy:   2.58 bits/char (printable)\n\
+             \x20 policy:    email = redact\n\
+             \x20 allowlist: 2 entries consulted, none matched\n\
+             \x20 outcome:   redacted as <EMAIL>"
+        );
+    }
+
+    #[test]
+    fn reports_rule_patterns_and_token_scores() {
+        let mut draft = RuleDraft::new("ticket", 1).unwrap();
+        draft.set("regex", r"TCK-\d+", 1).unwrap();
+        let options = ScrubOptions {
+            rules: vec![draft.build().unwrap()],
+            ..ScrubOptions::default()
+        };
+        let text = "TCK-42 api_key=Zq8vN2xLr5Tw9KmB3sHd7YcF1gJp4";
+        let found = explain(text, &options);
+        assert_eq!(found[0].pattern_id, "rule.ticket");
+        assert_eq!(found[0].pattern.as_deref(), Some(r"TCK-\d+"));
+        assert_eq!(found[0].outcome, Outcome::Redacted("<TICKET>".to_string()));
+        let token = found.iter().find(|e| e.kind == "token").unwrap();
+        assert_eq!(token.pattern_id, "builtin.token");
+        assert!(token.score.unwrap() >= token.threshold.unwrap());
+        assert_eq!(token.alphabet, "alphanumeric");
+    }
+}
@@ -0,0 +1,116 @@
+//! The git pre-commit hook written by `scrubby hook install`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Marks hooks written by scrubby, so they can be replaced or removed safely.
+const MARKER: &str = "# Installed by `scrubby hook install`";
+
+#[derive(Debug)]
+pub struct HookError {
+    pub message: String,
+}
+
+impl std::fmt::Display for HookError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// The hook script: checks the staged changes with `check_args` added to
+/// `scrubby check --staged`.
+pub fn hook_script(check_args: &[String]) -> String {
+    let mut command = String::from("exec scrubby check --staged");
+    for arg in check_args {
+        command.push(' ');
+        command.push_str(arg);
+    }
+    format!(
+        "#!/bin/sh\n{}; remove with `scrubby hook uninstall`.\n# Bypass once with `git commit --no-verify`.\n{}\n",
+        MARKER, command
+    )
+}
+
+/// Writes the pre-commit hook into `hooks_dir`. An existing hook that scrubby
+/// did not write is only replaced with `force`.
+pub fn install_hook(hooks_dir: &Path, script: &str, force: bool) -> Result<PathBuf, HookError> {
+    let path = hooks_dir.join("pre-commit");
+    if !force && path.exists() && !is_ours(&path) {
+        return Err(HookError {
+            message: format!(
+                "{} already exists and was not installed by scrubby (use --force to replace it)",
+                path.display()
+            ),
+        });
+    }
+    let write = fs::create_dir_all(hooks_dir)
+        .and_then(|_| fs::write(&path, script))
+        .and_then(|_| make_executable(&path));
+    write.map_err(|e| HookError {
+        message: format!("Failed to write {}: {}", path.display(), e),
+    })?;
+    Ok(path)
+}
+
+/// Removes the pre-commit hook from `hooks_dir` if scrubby installed it.
+pub fn uninstall_hook(hooks_dir: &Path) -> Result<PathBuf, HookError> {
+    let path = hooks_dir.join("pre-commit");
+    if !path.exists() {
+        return Err(HookError {
+            message: format!("No pre-commit hook at {}", path.display()),
+        });
+    }
+    if !is_ours(&path) {
+        return Err(HookError {
+            message: format!(
+                "{} was not installed by scrubby; leaving it in place",
+                path.display()
+            ),
+        });
+    }
+    fs::remove_file(&path).map_err(|e| HookError {
+        message: format!("Failed to remove {}: {}", path.display(), e),
+    })?;
+    Ok(path)
+}
+
+fn is_ours(path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|s| s.contains(MARKER))
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn installs_and_removes_only_its_own_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = dir.path().join("hooks");
+        let script = hook_script(&["--fail-on".to_string(), "high".to_string()]);
+        assert!(script.ends_with("exec scrubby check --staged --fail-on high\n"));
+
+        let path = install_hook(&hooks, &script, false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), script);
+        // Reinstalling over our own hook needs no --force.
+        install_hook(&hooks, &script, false).unwrap();
+        uninstall_hook(&hooks).unwrap();
+        assert!(!path.exists());
+
+        fs::write(&path, "#!/bin/sh\nmake lint\n").unwrap();
+        let err = install_hook(&hooks, &script, false).unwrap_err();
+        assert!(err.message.contains("use --force"));
+        assert!(uninstall_hook(&hooks).is_err());
+        install_hook(&hooks, &script, true).unwrap();
+    }
+}
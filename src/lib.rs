@@ -1,11 +1,30 @@
+pub mod allowlist;
 pub mod clipboard;
 pub mod config;
+pub mod denylist;
 pub mod detectors;
+pub mod diff;
+pub mod explain;
+pub mod hook;
 pub mod license;
+pub mod location;
+pub mod ndjson;
+pub mod normalize;
+pub mod policy;
+pub mod preview;
 pub mod redactor;
+pub mod report;
+pub mod review;
+pub mod rewrite;
+pub mod rules;
+pub mod sarif;
+pub mod scoring;
+pub mod severity;
+pub mod walk;
 
 use detectors::Detections;
-use redactor::RedactionResult;
+use redactor::{Finding, RedactionResult};
+use severity::Severity;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Summary {
@@ -14,17 +33,95 @@ pub struct Summary {
     pub uuids: usize,
     pub jwts: usize,
     pub tokens: usize,
+    pub terms: usize,
+    /// Matches of user-defined rules from the config file.
+    pub custom: usize,
+    /// Detected but kept because the allowlist matched; not part of `total`.
+    pub allowlisted: usize,
+    /// Left in place by an `allow` policy; not part of `total`.
+    pub allowed: usize,
+    /// Left in place by a `warn` policy; not part of `total`.
+    pub warned: usize,
+    /// Matched a `block` policy; also counted under their kind.
+    pub blocked: usize,
+    /// Findings (redacted or warned) per severity, indexed by `Severity as usize`.
+    pub severities: [usize; 4],
 }
 
 impl Summary {
     pub fn total(&self) -> usize {
-        self.emails + self.ips + self.uuids + self.jwts + self.tokens
+        self.emails + self.ips + self.uuids + self.jwts + self.tokens + self.terms + self.custom
+    }
+
+    /// Adds the counts of `other`, e.g. to total up several files.
+    pub fn add(&mut self, other: &Summary) {
+        self.emails += other.emails;
+        self.ips += other.ips;
+        self.uuids += other.uuids;
+        self.jwts += other.jwts;
+        self.tokens += other.tokens;
+        self.terms += other.terms;
+        self.custom += other.custom;
+        self.allowlisted += other.allowlisted;
+        self.allowed += other.allowed;
+        self.warned += other.warned;
+        self.blocked += other.blocked;
+        for (sum, n) in self.severities.iter_mut().zip(other.severities) {
+            *sum += n;
+        }
+    }
+
+    /// The most serious severity among the findings, if there are any.
+    pub fn max_severity(&self) -> Option<Severity> {
+        Severity::ALL
+            .into_iter()
+            .rev()
+            .find(|&s| self.severities[s as usize] > 0)
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone)]
 pub struct ScrubOptions {
     pub stable_placeholders: bool,
+    /// Built-in detectors to run and their parameters.
+    pub detectors: detectors::DetectorOptions,
+    /// Rejoin values split by line wraps, `\` continuations or `"a" + "b"`.
+    pub reassemble: bool,
+    /// Minimum confidence score (0.0..=1.0) for a generic token to be redacted.
+    pub token_threshold: f64,
+    /// Per-alphabet entropy floors a token candidate must clear to be scored.
+    pub entropy: scoring::EntropyThresholds,
+    /// Known-safe values that are detected but never replaced.
+    pub allowlist: allowlist::Allowlist,
+    /// Literal terms (names, codenames) that are always redacted.
+    pub denylist: denylist::Denylist,
+    /// User-defined regex rules, checked before the built-in detectors.
+    pub rules: Vec<rules::Rule>,
+    /// Per-kind actions: redact, warn, block or allow.
+    pub policy: policy::Policy,
+    /// Per-kind severities reported on findings.
+    pub severities: severity::Severities,
+    /// Exact `(start, end)` spans of the input to leave in place, such as
+    /// findings the user kept during a review. Counted as allowlisted.
+    pub kept: Vec<(usize, usize)>,
+}
+
+impl Default for ScrubOptions {
+    fn default() -> Self {
+        ScrubOptions {
+            stable_placeholders: false,
+            detectors: detectors::DetectorOptions::default(),
+            reassemble: false,
+            token_threshold: scoring::DEFAULT_TOKEN_THRESHOLD,
+            entropy: scoring::EntropyThresholds::default(),
+            allowlist: allowlist::Allowlist::default(),
+            denylist: denylist::Denylist::default(),
+            rules: Vec::new(),
+            policy: policy::Policy::default(),
+            severities: severity::Severities::default(),
+            kept: Vec::new(),
+        }
+    }
 }
 
 pub fn scrub_text(input: &str) -> (String, Summary) {
@@ -32,28 +129,103 @@ pub fn scrub_text(input: &str) -> (String, Summary) {
 }
 
 pub fn scrub_text_with_options(input: &str, options: ScrubOptions) -> (String, Summary) {
-    let detections: Detections = detectors::detect(input);
+    let (text, summary, _) = scrub_text_with_findings(input, &options);
+    (text, summary)
+}
+
+/// Like [`scrub_text_with_options`], but also returns every finding. Check
+/// `Summary::blocked` before using the text: blocking findings are redacted
+/// but the policy asks for no output at all.
+pub fn scrub_text_with_findings(
+    input: &str,
+    options: &ScrubOptions,
+) -> (String, Summary, Vec<Finding>) {
+    let detections: Detections = detectors::detect_with_options(input, options);
     let redacted: RedactionResult = redactor::redact(input, &detections, options);
 
-    let summary = Summary {
+    let mut summary = Summary {
         emails: redacted.counts.emails,
         ips: redacted.counts.ips,
         uuids: redacted.counts.uuids,
         jwts: redacted.counts.jwts,
         tokens: redacted.counts.tokens,
+        terms: redacted.counts.terms,
+        custom: redacted.counts.custom,
+        allowlisted: redacted.counts.allowlisted,
+        allowed: redacted.counts.allowed,
+        warned: redacted.counts.warned,
+        blocked: redacted.counts.blocked,
+        severities: [0; 4],
     };
+    for f in redacted.findings.iter() {
+        summary.severities[f.severity as usize] += 1;
+    }
 
-    (redacted.text, summary)
+    (redacted.text, summary, redacted.findings)
 }
 
 pub fn format_summary(summary: &Summary) -> String {
     let mut lines = Vec::new();
-    lines.push("Scrubby cleaned your clipboard:".to_string());
+    if summary.blocked > 0 {
+        lines.push("Scrubby blocked your clipboard:".to_string());
+    } else {
+        lines.push("Scrubby cleaned your clipboard:".to_string());
+    }
     lines.push(format!("- Emails: {}", summary.emails));
     lines.push(format!("- IPs: {}", summary.ips));
     lines.push(format!("- UUIDs: {}", summary.uuids));
     lines.push(format!("- JWTs: {}", summary.jwts));
     lines.push(format!("- Tokens: {}", summary.tokens));
-    lines.push("Safe to paste.".to_string());
+    if summary.custom > 0 {
+        lines.push(format!("- Custom rules: {}", summary.custom));
+    }
+    if summary.terms > 0 {
+        lines.push(format!("- Denylisted terms: {}", summary.terms));
+    }
+    if summary.allowlisted > 0 {
+        lines.push(format!("- Allowlisted (kept): {}", summary.allowlisted));
+    }
+    if summary.allowed > 0 {
+        lines.push(format!("- Allowed by policy (kept): {}", summary.allowed));
+    }
+    if summary.max_severity().is_some() {
+        let levels: Vec<String> = Severity::ALL
+            .iter()
+            .rev()
+            .filter(|&&s| summary.severities[s as usize] > 0)
+            .map(|&s| format!("{} {}", summary.severities[s as usize], s))
+            .collect();
+        lines.push(format!("- Severity: {}", levels.join(", ")));
+    }
+    if summary.warned > 0 {
+        lines.push(format!("- Left in place (warn policy): {}", summary.warned));
+    }
+    if summary.blocked > 0 {
+        lines.push(format!("- Blocked (block policy): {}", summary.blocked));
+        lines.push("Blocked: clipboard not modified with scrubbed text.".to_string());
+    } else if summary.warned > 0 {
+        lines.push("Review before pasting.".to_string());
+    } else {
+        lines.push("Safe to paste.".to_string());
+    }
     lines.join("\n")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocked_summary_is_never_safe_to_paste() {
+        let summary = Summary {
+            jwts: 1,
+            blocked: 1,
+            ..Summary::default()
+        };
+        let text = format_summary(&summary);
+        assert!(!text.contains("Safe to paste"), "{}", text);
+        assert!(!text.contains("cleaned"), "{}", text);
+        assert!(text.ends_with("Blocked: clipboard not modified with scrubbed text."));
+        assert!(format_summary(&Summary::default()).ends_with("Safe to paste."));
+    }
+}
@@ -237,7 +237,7 @@ fn hex_lower(bytes: &[u8]) -> String {
 mod tests {
     use super::*;
     use ed25519_dalek::{Signer, SigningKey};
-    use rand_core::OsRng;
+    use rand_core::{OsRng, RngCore};
     use std::fs;
     use std::path::PathBuf;
 
@@ -246,7 +246,7 @@ mod tests {
         std::env::set_var("SCRUBBY_LICENSE", "DEV");
         let ok = check_license().unwrap();
         if cfg!(debug_assertions) {
-            assert!(ok);
+            assert!(ok.is_some());
         } else {
             assert!(ok.is_none());
         }
@@ -256,7 +256,9 @@ mod tests {
     #[test]
     fn license_file_unblocks() {
         let dir = tempfile::tempdir().unwrap();
-        let signing = SigningKey::generate(&mut OsRng);
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let signing = SigningKey::from_bytes(&bytes);
         let verifying = signing.verifying_key();
         let payload = "email=test@example.com\nplan=pro\n";
         let signature = signing.sign(payload.as_bytes());
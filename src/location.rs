@@ -0,0 +1,59 @@
+/// Maps byte offsets in a text to 1-based line and column numbers.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// Byte offset where each line starts.
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> LineIndex<'a> {
+        let starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        LineIndex { text, starts }
+    }
+
+    /// `(line, column)` of `offset`; columns count characters, not bytes.
+    pub fn locate(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.starts.partition_point(|&s| s <= offset) - 1;
+        let column = self.text[self.starts[line]..offset].chars().count() + 1;
+        (line + 1, column)
+    }
+
+    /// The text of 1-based line `line`, without its line break.
+    pub fn line(&self, line: usize) -> &'a str {
+        let (start, end) = self.line_span(line);
+        self.text[start..end].trim_end_matches('\r')
+    }
+
+    /// Byte offsets of the start and end of line `line`, excluding the `\n`.
+    pub fn line_span(&self, line: usize) -> (usize, usize) {
+        let start = self.starts[line - 1];
+        let end = self.starts.get(line).map_or(self.text.len(), |&e| e - 1);
+        (start, end)
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.starts.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locates_offsets_by_line_and_char_column() {
+        let text = "first\nsé a@b.io\r\n\nlast";
+        let index = LineIndex::new(text);
+        assert_eq!(index.locate(0), (1, 1));
+        assert_eq!(index.locate(text.find("a@b").unwrap()), (2, 4));
+        assert_eq!(index.locate(text.find("last").unwrap()), (4, 1));
+        assert_eq!(index.line(2), "sé a@b.io");
+        assert_eq!(index.line(3), "");
+        assert_eq!(index.line_span(4), (text.len() - 4, text.len()));
+        assert_eq!(index.line_count(), 4);
+    }
+}
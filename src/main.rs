@@ -1,148 +1,685 @@
+mod cli;
+
+use clap::{ColorChoice, Parser};
+use cli::{
+    CheckArgs, CheckFormat, Cli, Command, ConfigArgs, ConfigCommand, ExplainArgs, HookCommand,
+    LicenseCommand, ScrubArgs, ScrubSettings, Stream, WatchArgs,
+};
+use scrubby::allowlist::{hash_value, AllowEntry};
 use scrubby::clipboard::{read_clipboard, write_clipboard};
-use scrubby::config::load_config;
+use scrubby::config::{
+    append_global_allowlist, discover, effective_entries, load_denylist, load_global_allowlist,
+    select_profile, starter_config, validate_config, Config, Layer, DEFAULT_INTERVAL_MS,
+    PROFILE_VAR,
+};
+use scrubby::denylist::Denylist;
+use scrubby::diff::parse_unified_diff;
+use scrubby::explain::{explain, format_explanation, Explanation, Outcome};
+use scrubby::hook::{hook_script, install_hook, uninstall_hook};
 use scrubby::license::{check_license, current_device_id, LicenseInfo};
-use scrubby::{format_summary, scrub_text_with_options, ScrubOptions, Summary};
-use std::io::{self, Read};
-use std::path::PathBuf;
-
-fn print_usage() {
-    eprintln!("Usage: scrubby [--clipboard | --watch] [options]");
-    eprintln!("\nOptions:");
-    eprintln!("  --clipboard    Sanitize current clipboard text");
-    eprintln!("  --watch        Watch clipboard and sanitize on change (experimental)");
-    eprintln!("  --device-id    Print device id for license binding");
-    eprintln!("  --interval-ms  Poll interval for --watch (default: 750)");
-    eprintln!("  --stdin        Read from stdin and print sanitized text");
-    eprintln!("  --file <path>  Read file and print sanitized text");
-    eprintln!("  --json         Print JSON report instead of text summary");
-    eprintln!("  --stable       Use stable placeholders (e.g., <EMAIL_1>)");
-    eprintln!("  --config <path>  Load config file");
-}
+use scrubby::location::LineIndex;
+use scrubby::ndjson::NdjsonWriter;
+use scrubby::policy::Action;
+use scrubby::preview::{preview, PreviewOptions};
+use scrubby::redactor::Finding;
+use scrubby::report::{ReportBuilder, Source};
+use scrubby::review::Reviewer;
+use scrubby::rewrite::{
+    backup, is_backup, mirror_collision, mirror_path, write_atomic, DEFAULT_BACKUP_SUFFIX,
+};
+use scrubby::sarif::SarifBuilder;
+use scrubby::severity::Severity;
+use scrubby::walk::{read_text, walk, Skip};
+use scrubby::{format_summary, scrub_text_with_findings, ScrubOptions, Summary};
+use std::fs::File;
+use std::io::{self, BufReader, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Once;
 
 fn main() {
-    let mut args = std::env::args().skip(1);
-    let mut mode: Option<String> = None;
-    let mut interval_ms: u64 = 750;
-    let mut json = false;
-    let mut stable = false;
-    let mut config_path: Option<PathBuf> = None;
-    let mut file_path: Option<PathBuf> = None;
-    let mut stdin_mode = false;
-
-    while let Some(arg) = args.next() {
-        match arg.as_str() {
-            "--clipboard" | "--watch" => {
-                if mode.is_some() {
-                    print_usage();
-                    std::process::exit(1);
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => {
+            // Usage errors keep exiting with 1; 2 and 3 mean clipboard failures.
+            let code = if e.use_stderr() { 1 } else { 0 };
+            let _ = e.print();
+            std::process::exit(code);
+        }
+    };
+    let command = match cli.command {
+        Some(command) => command,
+        None => cli.legacy.into_command(),
+    };
+    match command {
+        Command::Scrub(args) => run_scrub(args),
+        Command::Watch(args) => run_watch_command(args),
+        Command::Check(args) => run_check(args),
+        Command::Explain(args) => run_explain(args),
+        Command::Hook(command) => run_hook_command(command),
+        Command::Config(command) => run_config_command(command),
+        Command::License(command) => run_license_command(command),
+    }
+}
+
+fn run_scrub(args: ScrubArgs) {
+    let file_stdin = args.stdin || !args.file.is_empty();
+    let text_to_stdout =
+        args.dry_run || args.stdin || (file_stdin && !args.in_place && args.output_dir.is_none());
+    if args.ndjson == Some(Stream::Stdout) && text_to_stdout {
+        eprintln!("Scrubby error: --ndjson=stdout needs the scrubbed text to go elsewhere (the clipboard, --in-place or --output-dir)");
+        std::process::exit(1);
+    }
+    let mut setup = prepare(args.settings, file_stdin);
+    // The records replace the JSON report, even one a config asks for.
+    let ndjson = args.ndjson.map(|stream| {
+        setup.json = false;
+        open_ndjson(stream)
+    });
+    if args.dry_run {
+        let inputs = if args.stdin {
+            vec![stdin_input()]
+        } else if args.file.is_empty() {
+            vec![clipboard_input()]
+        } else {
+            let max_file_size = args.walk.max_file_size;
+            args.file
+                .iter()
+                .flat_map(|p| file_inputs(p, max_file_size))
+                .collect()
+        };
+        let preview = PreviewOptions {
+            style: args.diff,
+            mask: args.mask,
+            color: match args.color {
+                ColorChoice::Always => true,
+                ColorChoice::Never => false,
+                ColorChoice::Auto => {
+                    io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
                 }
-                mode = Some(arg);
-            }
-            "--interval-ms" => {
-                let v = match args.next() {
-                    Some(v) => v,
-                    None => {
-                        print_usage();
-                        std::process::exit(1);
-                    }
-                };
-                interval_ms = match v.parse::<u64>() {
-                    Ok(n) if n >= 100 => n,
-                    _ => {
-                        eprintln!("Scrubby error: --interval-ms must be >= 100");
-                        std::process::exit(1);
-                    }
-                };
-            }
-            "--json" => {
-                json = true;
-            }
-            "--stable" => {
-                stable = true;
+            },
+        };
+        run_dry_run(inputs, &preview, &setup, args.fail_on, ndjson);
+        return;
+    }
+    let mut review = args.review.then(|| open_review(args.save_allowlist));
+    if args.stdin {
+        let options = &setup.options;
+        run_stdin(setup.json, args.fail_on, options, &mut review, ndjson);
+    } else if args.file.is_empty() {
+        let options = &setup.options;
+        run_once(setup.json, args.fail_on, options, &mut review, ndjson);
+    } else {
+        let dest = if args.in_place {
+            Destination::InPlace(args.backup)
+        } else if let Some(dir) = args.output_dir {
+            Destination::Mirror(dir)
+        } else if args.file.len() == 1 && !args.file[0].is_dir() {
+            run_file(
+                &args.file[0],
+                setup.json,
+                args.fail_on,
+                &setup.options,
+                &mut review,
+                ndjson,
+            );
+            return;
+        } else {
+            Destination::Stdout
+        };
+        run_files(
+            &args.file,
+            args.walk.max_file_size,
+            dest,
+            &setup,
+            args.fail_on,
+            &mut review,
+            ndjson,
+        );
+    }
+}
+
+/// `--review` state: the reviewer reading answers from the terminal.
+struct Review {
+    reviewer: Reviewer<BufReader<File>, io::Stderr>,
+    save_allowlist: bool,
+}
+
+#[cfg(windows)]
+const TERMINAL: &str = "CONIN$";
+#[cfg(not(windows))]
+const TERMINAL: &str = "/dev/tty";
+
+/// Answers come from the terminal even when stdin is the text to scrub.
+fn open_review(save_allowlist: bool) -> Review {
+    match File::open(TERMINAL) {
+        Ok(tty) => Review {
+            reviewer: Reviewer::new(BufReader::new(tty), io::stderr()),
+            save_allowlist,
+        },
+        Err(e) => {
+            eprintln!("Scrubby error: --review needs a terminal ({})", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Scrubs `text`, first asking about each finding when reviewing.
+fn scrub_input(
+    name: &str,
+    text: &str,
+    options: &ScrubOptions,
+    review: &mut Option<Review>,
+) -> (String, Summary, Vec<Finding>) {
+    let Some(review) = review else {
+        return scrub_text_with_findings(text, options);
+    };
+    let reviewed = match review.reviewer.review(name, text, options) {
+        Ok(reviewed) => reviewed,
+        Err(e) => {
+            eprintln!("Scrubby error: Review failed: {}", e);
+            std::process::exit(2);
+        }
+    };
+    let allowed = review.reviewer.take_allowed();
+    if review.save_allowlist && !allowed.is_empty() {
+        // Hashed, so the allowlist does not hold the values themselves.
+        let entries: Vec<AllowEntry> = allowed.iter().map(|v| AllowEntry::hashed(v)).collect();
+        match append_global_allowlist(&entries) {
+            Ok(path) => eprintln!(
+                "Added {} to {}",
+                count_noun(entries.len(), "value"),
+                path.display()
+            ),
+            Err(e) => eprintln!("Scrubby error: {}", e),
+        }
+    }
+    scrub_text_with_findings(text, &reviewed)
+}
+
+/// Where `scrub --file` puts sanitized text.
+enum Destination {
+    /// Each file after a `==> path <==` header.
+    Stdout,
+    /// Over the original, after a backup with this suffix if given.
+    InPlace(Option<String>),
+    /// Below this directory, at the file's path relative to its argument.
+    Mirror(PathBuf),
+}
+
+fn run_watch_command(args: WatchArgs) {
+    let setup = prepare(args.settings, false);
+    let interval_ms = args.interval_ms.unwrap_or(setup.interval_ms);
+    run_watch(interval_ms, setup.json, &setup.options);
+}
+
+/// Prints every finding as `path:line:column: severity kind` and exits with
+/// [`FAIL_ON_EXIT`] when more than `--max-findings` reach `--fail-on`, or 2
+/// when an input could not be read. Nothing is written anywhere else.
+///
+/// Since it never outputs scrubbed text, check has no Pro gates of its own:
+/// files, stdin and every `--format` work in all builds (`--config` and
+/// `--profile` still need pro-config).
+fn run_check(args: CheckArgs) {
+    let settings = ScrubSettings {
+        reassemble: args.reassemble,
+        denylist: args.denylist,
+        config: args.config,
+        ..ScrubSettings::default()
+    };
+    let setup = prepare(settings, false);
+    let inputs = if args.staged {
+        staged_inputs()
+    } else if args.paths.is_empty() {
+        vec![stdin_input()]
+    } else {
+        let mut inputs = Vec::new();
+        for path in args.paths.iter() {
+            if path == Path::new("-") {
+                inputs.push(stdin_input());
+            } else {
+                inputs.extend(file_inputs(path, args.walk.max_file_size));
             }
-            "--config" => {
-                let v = match args.next() {
-                    Some(v) => v,
-                    None => {
-                        print_usage();
-                        std::process::exit(1);
-                    }
-                };
-                config_path = Some(PathBuf::from(v));
+        }
+        inputs
+    };
+
+    let mut sarif = (args.format == CheckFormat::Sarif).then(|| SarifBuilder::new(&setup.options));
+    let mut ndjson = (args.format == CheckFormat::Ndjson).then(|| open_ndjson(Stream::Stdout));
+    let mut total = Summary::default();
+    let mut read_failed = false;
+    let mut skipped = 0usize;
+    for input in inputs.iter() {
+        let text = match &input.content {
+            Content::Text(s) => s,
+            Content::Skipped(why) => {
+                eprintln!("Skipped {}: {}", input.name, why);
+                if let Some(sarif) = sarif.as_mut() {
+                    sarif.skipped(&input.name, &why.to_string());
+                }
+                if let Some(stream) = ndjson.as_mut() {
+                    ndjson_written(stream.skipped(&input.name, &why.to_string()));
+                }
+                skipped += 1;
+                continue;
             }
-            "--file" => {
-                let v = match args.next() {
-                    Some(v) => v,
-                    None => {
-                        print_usage();
-                        std::process::exit(1);
-                    }
-                };
-                file_path = Some(PathBuf::from(v));
+            Content::Failed(e) => {
+                eprintln!("Scrubby error: {}", e);
+                if let Some(sarif) = sarif.as_mut() {
+                    sarif.failed(e);
+                }
+                read_failed = true;
+                continue;
             }
-            "--stdin" => {
-                stdin_mode = true;
+        };
+        let (_, summary, findings) = scrub_text_with_findings(text, &setup.options);
+        total.add(&summary);
+        if let Some(sarif) = sarif.as_mut() {
+            let numbers = input.line_numbers.as_deref();
+            sarif.input(&setup.options, &input.name, text, numbers, &findings);
+            continue;
+        }
+        if let Some(stream) = ndjson.as_mut() {
+            let numbers = input.line_numbers.as_deref();
+            let source = input.source;
+            let written = stream.input(&input.name, source, text, numbers, &summary, &findings);
+            ndjson_written(written);
+            continue;
+        }
+        let index = LineIndex::new(text);
+        for f in findings.iter() {
+            let (mut line, column) = index.locate(f.start);
+            if let Some(numbers) = input.line_numbers.as_ref() {
+                line = numbers[line - 1];
             }
-            "--device-id" => {
-                let id = match current_device_id() {
-                    Ok(v) => v,
-                    Err(e) => {
-                        eprintln!("Scrubby error: {}", e);
-                        std::process::exit(1);
-                    }
-                };
-                println!("{}", id);
-                return;
+            let rule = f
+                .rule
+                .as_deref()
+                .map(|r| format!(" ({})", r))
+                .unwrap_or_default();
+            println!(
+                "{}:{}:{}: {} {}{}",
+                input.name, line, column, f.severity, f.kind, rule
+            );
+        }
+    }
+    if let Some(sarif) = sarif {
+        println!("{}", sarif.finish().to_json());
+    }
+    if let Some(stream) = ndjson {
+        ndjson_written(stream.finish());
+    }
+
+    let counted: usize = Severity::ALL
+        .iter()
+        .filter(|&&s| s >= args.fail_on)
+        .map(|&s| total.severities[s as usize])
+        .sum();
+    let found: usize = total.severities.iter().sum();
+    eprintln!(
+        "{} finding(s) in {}{}",
+        found,
+        count_noun(inputs.len() - skipped, "input"),
+        skipped_note(skipped)
+    );
+    if read_failed {
+        std::process::exit(2);
+    }
+    if counted > args.max_findings {
+        eprintln!(
+            "Scrubby check failed: {} finding(s) at or above {} severity (allowed: {})",
+            counted, args.fail_on, args.max_findings
+        );
+        std::process::exit(FAIL_ON_EXIT);
+    }
+}
+
+/// Text to scan, with the original line number of each of its lines when it
+/// is not a whole file.
+struct CheckInput {
+    name: String,
+    source: Source,
+    /// The file read, for inputs that are whole files.
+    path: Option<PathBuf>,
+    content: Content,
+    line_numbers: Option<Vec<usize>>,
+}
+
+enum Content {
+    Text(String),
+    Skipped(Skip),
+    Failed(String),
+}
+
+fn stdin_input() -> CheckInput {
+    let mut input = String::new();
+    let content = match io::stdin().read_to_string(&mut input) {
+        Ok(_) => Content::Text(input),
+        Err(e) => Content::Failed(format!("<stdin>: {}", e)),
+    };
+    CheckInput {
+        name: "<stdin>".to_string(),
+        source: Source::Stdin,
+        path: None,
+        content,
+        line_numbers: None,
+    }
+}
+
+/// Prints an explanation of every detected value, as text or with `--json`
+/// as `{"inputs":[{"name","findings":[...]}]}`.
+fn run_explain(args: ExplainArgs) {
+    let file_stdin = args.stdin || !args.file.is_empty();
+    let setup = prepare(args.settings, file_stdin);
+    let inputs = if args.stdin {
+        vec![stdin_input()]
+    } else if args.file.is_empty() {
+        vec![clipboard_input()]
+    } else {
+        let max_file_size = args.walk.max_file_size;
+        args.file
+            .iter()
+            .flat_map(|p| file_inputs(p, max_file_size))
+            .collect()
+    };
+    let mut reports = Vec::new();
+    let mut read_failed = false;
+    let mut blocks = Vec::new();
+    for input in inputs {
+        let text = match input.content {
+            Content::Text(s) => s,
+            Content::Skipped(why) => {
+                eprintln!("Skipped {}: {}", input.name, why);
+                continue;
             }
-            _ => {
-                print_usage();
-                std::process::exit(1);
+            Content::Failed(e) => {
+                eprintln!("Scrubby error: {}", e);
+                read_failed = true;
+                continue;
             }
+        };
+        let explanations = explain(&text, &setup.options);
+        if setup.json {
+            let findings: Vec<serde_json::Value> =
+                explanations.iter().map(explanation_json).collect();
+            reports.push(serde_json::json!({ "name": input.name, "findings": findings }));
+            continue;
+        }
+        for e in explanations.iter() {
+            blocks.push(format_explanation(&input.name, e));
         }
+        if explanations.is_empty() {
+            eprintln!("{}: nothing detected", input.name);
+        }
+    }
+    if setup.json {
+        println!("{}", serde_json::json!({ "inputs": reports }));
+    } else if !blocks.is_empty() {
+        println!("{}", blocks.join("\n\n"));
     }
+    if read_failed {
+        std::process::exit(2);
+    }
+}
 
-    let mode = mode.unwrap_or_else(|| "--clipboard".to_string());
+fn explanation_json(e: &Explanation) -> serde_json::Value {
+    serde_json::json!({
+        "kind": e.kind,
+        "rule": e.rule,
+        "pattern_id": e.pattern_id,
+        "pattern": e.pattern,
+        "span": { "start": e.start, "end": e.end },
+        "line": e.line,
+        "column": e.column,
+        "value": e.value,
+        "severity": e.severity.name(),
+        "entropy": e.entropy,
+        "alphabet": e.alphabet,
+        "entropy_floor": e.entropy_floor,
+        "score": e.score,
+        "threshold": e.threshold,
+        "policy": e.policy,
+        "allowlist": {
+            "consulted": e.consulted,
+            "matched": match &e.outcome {
+                Outcome::Allowlisted(entry) => Some(entry),
+                _ => None,
+            },
+        },
+        "outcome": e.outcome.name(),
+        "placeholder": e.outcome.placeholder(),
+    })
+}
 
-    if stdin_mode && file_path.is_some() {
-        eprintln!("Scrubby error: --stdin and --file are mutually exclusive");
-        std::process::exit(1);
+fn clipboard_input() -> CheckInput {
+    CheckInput {
+        name: "clipboard".to_string(),
+        source: Source::Clipboard,
+        path: None,
+        content: match read_clipboard() {
+            Ok(text) => Content::Text(text),
+            Err(e) => Content::Failed(e.to_string()),
+        },
+        line_numbers: None,
     }
+}
 
-    if mode == "--watch" && (stdin_mode || file_path.is_some()) {
-        eprintln!("Scrubby error: --watch cannot be used with --stdin or --file");
-        std::process::exit(1);
+/// `path` itself, or every file under it that is not ignored.
+fn file_inputs(path: &Path, max_file_size: u64) -> Vec<CheckInput> {
+    let (files, errors) = walk(&[path.to_path_buf()]);
+    let failed = errors.into_iter().map(|e| CheckInput {
+        name: path.display().to_string(),
+        source: Source::File,
+        path: None,
+        content: Content::Failed(e.message),
+        line_numbers: None,
+    });
+    let read = files.into_iter().map(|file| {
+        let content = match read_text(&file, max_file_size) {
+            Ok(Ok(text)) => Content::Text(text),
+            Ok(Err(skip)) => Content::Skipped(skip),
+            Err(e) => Content::Failed(format!("{}: {}", file.display(), e)),
+        };
+        CheckInput {
+            name: file.display().to_string(),
+            source: Source::File,
+            path: Some(file),
+            content,
+            line_numbers: None,
+        }
+    });
+    read.chain(failed).collect()
+}
+
+fn count_noun(n: usize, noun: &str) -> String {
+    format!("{} {}{}", n, noun, if n == 1 { "" } else { "s" })
+}
+
+fn skipped_note(skipped: usize) -> String {
+    match skipped {
+        0 => String::new(),
+        n => format!(" ({} skipped)", n),
+    }
+}
+
+/// The lines added by the staged changes, one input per file.
+fn staged_inputs() -> Vec<CheckInput> {
+    let diff = git(&[
+        "diff",
+        "--cached",
+        "--no-color",
+        "--no-ext-diff",
+        "--unified=0",
+        // Fixed prefixes, whatever diff.noprefix or diff.mnemonicPrefix say.
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+    ]);
+    let files = match parse_unified_diff(&diff) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Scrubby error: {}", e);
+            std::process::exit(2);
+        }
+    };
+    files
+        .into_iter()
+        .map(|file| {
+            let (text, numbers) = file.text();
+            CheckInput {
+                name: file.path,
+                source: Source::File,
+                path: None,
+                content: Content::Text(text),
+                line_numbers: Some(numbers),
+            }
+        })
+        .collect()
+}
+
+/// Runs git and returns its stdout, exiting with 2 if it fails.
+fn git(args: &[&str]) -> String {
+    let output = std::process::Command::new("git").args(args).output();
+    match output {
+        Ok(out) if out.status.success() => String::from_utf8_lossy(&out.stdout).into_owned(),
+        Ok(out) => {
+            eprintln!(
+                "Scrubby error: git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+            std::process::exit(2);
+        }
+        Err(e) => {
+            eprintln!("Scrubby error: Failed to run git: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
+
+fn run_hook_command(command: HookCommand) {
+    // `--git-path` follows worktrees and core.hooksPath.
+    let hooks_dir = PathBuf::from(git(&["rev-parse", "--git-path", "hooks"]).trim());
+    let result = match command {
+        HookCommand::Install {
+            force,
+            fail_on,
+            max_findings,
+        } => {
+            let mut check_args = Vec::new();
+            if let Some(s) = fail_on {
+                check_args.extend(["--fail-on".to_string(), s.to_string()]);
+            }
+            if let Some(n) = max_findings {
+                check_args.extend(["--max-findings".to_string(), n.to_string()]);
+            }
+            install_hook(&hooks_dir, &hook_script(&check_args), force)
+                .map(|p| format!("Installed pre-commit hook at {}", p.display()))
+        }
+        HookCommand::Uninstall => uninstall_hook(&hooks_dir)
+            .map(|p| format!("Removed pre-commit hook at {}", p.display())),
+    };
+    match result {
+        Ok(message) => println!("{}", message),
+        Err(e) => {
+            eprintln!("Scrubby error: {}", e);
+            std::process::exit(1);
+        }
     }
+}
+
+/// Options and output settings once config files and flags are combined.
+struct Setup {
+    options: ScrubOptions,
+    json: bool,
+    interval_ms: u64,
+}
 
+/// Loads the config, checks Pro gates and builds the scrub options; flags
+/// win over every config layer.
+fn prepare(settings: ScrubSettings, file_stdin: bool) -> Setup {
     let license = apply_feature_gates(
-        json,
-        stable,
-        config_path.is_some(),
-        stdin_mode || file_path.is_some(),
+        settings.json,
+        settings.stable,
+        uses_config(&settings.config),
+        file_stdin,
     );
+    let layers = load_layers(&settings.config);
 
-    let mut options = ScrubOptions::default();
-    if let Some(path) = config_path.as_ref() {
-        match load_config(path) {
-            Ok(cfg) => {
-                if let Some(v) = cfg.stable_placeholders {
-                    options.stable_placeholders = v;
-                }
-                if let Some(v) = cfg.json_report {
-                    json = v;
-                }
-                if let Some(v) = cfg.interval_ms {
-                    interval_ms = v;
+    let mut setup = Setup {
+        options: ScrubOptions::default(),
+        json: settings.json,
+        interval_ms: DEFAULT_INTERVAL_MS,
+    };
+    match load_global_allowlist() {
+        Ok(allowlist) => setup.options.allowlist = allowlist,
+        Err(e) => {
+            eprintln!("Scrubby error: {}", e);
+            std::process::exit(1);
+        }
+    }
+    let mut denylist_paths = settings.denylist;
+    if !layers.is_empty() {
+        let mut cfg = Config::default();
+        for layer in layers {
+            cfg.merge(layer.config);
+        }
+        let mut licensed = None;
+        if cfg.stable_placeholders == Some(true)
+            && !config_may_enable(
+                "stable_placeholders",
+                cfg!(feature = "pro-stable-placeholders"),
+                &license,
+                &mut licensed,
+            )
+        {
+            cfg.stable_placeholders = None;
+        }
+        if cfg.json_report == Some(true)
+            && !config_may_enable(
+                "json_report",
+                cfg!(feature = "pro-json-report"),
+                &license,
+                &mut licensed,
+            )
+        {
+            cfg.json_report = None;
+        }
+        cfg.apply(&mut setup.options);
+        if let Some(p) = cfg.denylist {
+            denylist_paths.insert(0, p);
+        }
+        if let Some(v) = cfg.json_report {
+            setup.json = v || settings.json;
+        }
+        if let Some(v) = cfg.interval_ms {
+            setup.interval_ms = v;
+        }
+    }
+
+    if !denylist_paths.is_empty() {
+        let mut entries = Vec::new();
+        for path in denylist_paths.iter() {
+            match load_denylist(path) {
+                Ok(e) => entries.extend(e),
+                Err(e) => {
+                    eprintln!("Scrubby error: {}", e);
+                    std::process::exit(1);
                 }
             }
+        }
+        setup.options.denylist = match Denylist::new(entries) {
+            Ok(d) => d,
             Err(e) => {
                 eprintln!("Scrubby error: {}", e);
                 std::process::exit(1);
             }
-        }
+        };
     }
 
-    if stable {
-        options.stable_placeholders = true;
+    if settings.stable {
+        setup.options.stable_placeholders = true;
+    }
+    if settings.reassemble {
+        setup.options.reassemble = true;
     }
 
     if let Some(info) = license.as_ref() {
@@ -152,24 +689,178 @@ fn main() {
             eprintln!("Scrubby Pro license verified");
         }
     }
+    setup
+}
 
-    if stdin_mode {
-        run_stdin(json, options);
-        return;
+/// Whether a Pro setting that a config file turns on applies: the build has
+/// its feature and a license is installed. Otherwise it is dropped with a
+/// warning, so a shared config does not break every run of other builds.
+/// `licensed` caches the license check.
+fn config_may_enable(
+    key: &str,
+    built: bool,
+    license: &Option<LicenseInfo>,
+    licensed: &mut Option<bool>,
+) -> bool {
+    let ok = built
+        && (license.is_some()
+            || *licensed.get_or_insert_with(|| matches!(check_license(), Ok(Some(_)))));
+    if !ok {
+        eprintln!(
+            "Scrubby warning: ignoring {} from config (needs a Pro build and license)",
+            key
+        );
+    }
+    ok
+}
+
+/// The discovered config layers, with the selected profile (`--profile` or
+/// `SCRUBBY_PROFILE`) applied. Discovery runs in every build.
+fn load_layers(args: &ConfigArgs) -> Vec<Layer> {
+    let discovered = discover(args.config.as_deref()).and_then(|mut layers| {
+        if let Some(name) = profile_name(args) {
+            select_profile(&mut layers, &name)?;
+        }
+        Ok(layers)
+    });
+    match discovered {
+        Ok(layers) => layers,
+        Err(e) => {
+            eprintln!("Scrubby error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// `--profile`, or else `SCRUBBY_PROFILE`, which builds without pro-config
+/// ignore with a warning.
+fn profile_name(args: &ConfigArgs) -> Option<String> {
+    static IGNORED: Once = Once::new();
+    if args.profile.is_some() {
+        return args.profile.clone();
+    }
+    let name = std::env::var(PROFILE_VAR).ok().filter(|v| !v.is_empty())?;
+    if !cfg!(feature = "pro-config") {
+        IGNORED.call_once(|| {
+            eprintln!(
+                "Scrubby warning: ignoring {}={} (profiles are a Pro feature)",
+                PROFILE_VAR, name
+            )
+        });
+        return None;
+    }
+    Some(name)
+}
+
+/// Whether the invocation picks a config file or profile, which is a Pro
+/// feature; discovered files and `SCRUBBY_*` overrides are not.
+fn uses_config(args: &ConfigArgs) -> bool {
+    args.config.is_some() || profile_name(args).is_some()
+}
+
+fn run_config_command(command: ConfigCommand) {
+    match command {
+        ConfigCommand::Init { path, force } => config_init(&path, force),
+        ConfigCommand::Validate { path } => config_validate(&path),
+        ConfigCommand::Show(args) => {
+            apply_feature_gates(false, false, uses_config(&args), false);
+            let layers = load_layers(&args);
+            print_effective_config(&layers);
+        }
+        ConfigCommand::HashValue { value } => println!("allow_sha256={}", hash_value(&value)),
+    }
+}
+
+fn run_license_command(command: LicenseCommand) {
+    match command {
+        LicenseCommand::Status => match check_license() {
+            Ok(Some(info)) => print_license(&info),
+            Ok(None) => {
+                eprintln!("No Pro license found (expected ~/.config/scrubby/license.key)");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Scrubby error: {}", e);
+                std::process::exit(3);
+            }
+        },
+        LicenseCommand::DeviceId => match current_device_id() {
+            Ok(id) => println!("{}", id),
+            Err(e) => {
+                eprintln!("Scrubby error: {}", e);
+                std::process::exit(1);
+            }
+        },
     }
-    if let Some(path) = file_path {
-        run_file(&path, json, options);
+}
+
+fn print_license(info: &LicenseInfo) {
+    println!("Scrubby Pro license verified");
+    for (name, value) in [
+        ("Licensed to", &info.email),
+        ("Plan", &info.plan),
+        ("Expires", &info.expires),
+        ("Device", &info.device_id),
+    ] {
+        if let Some(v) = value {
+            println!("{}: {}", name, v);
+        }
+    }
+}
+
+/// Writes the starter config to `path`, or to stdout for `-`.
+fn config_init(path: &Path, force: bool) {
+    if path == Path::new("-") {
+        print!("{}", starter_config());
         return;
     }
+    if !force && path.exists() {
+        eprintln!(
+            "Scrubby error: {} already exists (use --force to overwrite)",
+            path.display()
+        );
+        std::process::exit(1);
+    }
+    if let Err(e) = std::fs::write(path, starter_config()) {
+        eprintln!("Scrubby error: Failed to write {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+    println!("Wrote starter config to {}", path.display());
+}
 
-    if mode == "--clipboard" {
-        run_once(json, options);
-    } else {
-        run_watch(interval_ms, json, options);
+fn config_validate(path: &Path) {
+    match validate_config(path) {
+        Ok(cfg) => {
+            println!(
+                "{}: OK ({} rules, {} profiles)",
+                path.display(),
+                cfg.rules.len(),
+                cfg.profiles.len()
+            );
+        }
+        Err(errors) => {
+            for e in errors.iter() {
+                let file = e.file.as_deref().unwrap_or(path);
+                eprintln!("{}: {}", file.display(), e);
+            }
+            eprintln!(
+                "{} error{} found",
+                errors.len(),
+                if errors.len() == 1 { "" } else { "s" }
+            );
+            std::process::exit(1);
+        }
     }
 }
 
-fn run_once(json: bool, options: ScrubOptions) {
+fn run_once(
+    json: bool,
+    fail_on: Option<Severity>,
+    options: &ScrubOptions,
+    review: &mut Option<Review>,
+    ndjson: Option<Ndjson>,
+) {
+    let report = ReportBuilder::new(options);
     let input = match read_clipboard() {
         Ok(s) => s,
         Err(e) => {
@@ -178,18 +869,27 @@ fn run_once(json: bool, options: ScrubOptions) {
         }
     };
 
-    let (sanitized, summary) = scrub_text_with_options(&input, options);
+    let (sanitized, summary, findings) = scrub_input("clipboard", &input, options, review);
+
+    if summary.blocked > 0 {
+        report_block(options, &findings);
+        clipboard_on_block(options);
+        let source = (Source::Clipboard, "clipboard", input.as_str());
+        output_report(json, report, ndjson, source, &summary, &findings);
+        std::process::exit(BLOCKED_EXIT);
+    }
 
     if let Err(e) = write_clipboard(&sanitized) {
         eprintln!("Scrubby error: {}", e);
         std::process::exit(3);
     }
 
-    // TODO(pro-json-report): support --json output
-    output_report(json, &summary, None);
+    let source = (Source::Clipboard, "clipboard", input.as_str());
+    output_report(json, report, ndjson, source, &summary, &findings);
+    check_fail_on(&summary, fail_on);
 }
 
-fn run_watch(interval_ms: u64, json: bool, options: ScrubOptions) {
+fn run_watch(interval_ms: u64, json: bool, options: &ScrubOptions) {
     let mut last_seen = String::new();
     let mut last_written = String::new();
     loop {
@@ -202,15 +902,20 @@ fn run_watch(interval_ms: u64, json: bool, options: ScrubOptions) {
         };
 
         if input != last_seen {
+            let report = ReportBuilder::new(options);
             last_seen = input.clone();
-            let (sanitized, summary) = scrub_text_with_options(&input, options);
-            if sanitized != input && sanitized != last_written {
+            let (sanitized, summary, findings) = scrub_text_with_findings(&input, options);
+            if summary.blocked > 0 {
+                report_block(options, &findings);
+                clipboard_on_block(options);
+            } else if sanitized != input && sanitized != last_written {
                 if let Err(e) = write_clipboard(&sanitized) {
                     eprintln!("Scrubby error: {}", e);
                     std::process::exit(3);
                 }
                 last_written = sanitized;
-                output_report(json, &summary, None);
+                let source = (Source::Clipboard, "clipboard", input.as_str());
+                output_report(json, report, None, source, &summary, &findings);
             }
         }
 
@@ -218,20 +923,47 @@ fn run_watch(interval_ms: u64, json: bool, options: ScrubOptions) {
     }
 }
 
-fn run_stdin(json: bool, options: ScrubOptions) {
+fn run_stdin(
+    json: bool,
+    fail_on: Option<Severity>,
+    options: &ScrubOptions,
+    review: &mut Option<Review>,
+    ndjson: Option<Ndjson>,
+) {
+    let mut report = ReportBuilder::new(options);
     let mut input = String::new();
     if let Err(e) = io::stdin().read_to_string(&mut input) {
         eprintln!("Scrubby error: {}", e);
         std::process::exit(2);
     }
-    let (sanitized, summary) = scrub_text_with_options(&input, options);
-    println!("{}", sanitized);
+    let (sanitized, summary, findings) = scrub_input("<stdin>", &input, options, review);
+    if summary.blocked == 0 {
+        println!("{}", sanitized);
+    }
     if json {
-        eprintln!("{}", json_report(&summary));
+        report.input("<stdin>", Source::Stdin, &input, &summary, &findings);
+        eprintln!("{}", report.finish().to_json());
+    }
+    if let Some(mut stream) = ndjson {
+        let written = stream.input("<stdin>", Source::Stdin, &input, None, &summary, &findings);
+        ndjson_written(written.and_then(|_| stream.finish()));
     }
+    if summary.blocked > 0 {
+        report_block(options, &findings);
+        std::process::exit(BLOCKED_EXIT);
+    }
+    check_fail_on(&summary, fail_on);
 }
 
-fn run_file(path: &PathBuf, json: bool, options: ScrubOptions) {
+fn run_file(
+    path: &PathBuf,
+    json: bool,
+    fail_on: Option<Severity>,
+    options: &ScrubOptions,
+    review: &mut Option<Review>,
+    ndjson: Option<Ndjson>,
+) {
+    let mut report = ReportBuilder::new(options);
     let input = match std::fs::read_to_string(path) {
         Ok(s) => s,
         Err(e) => {
@@ -239,30 +971,404 @@ fn run_file(path: &PathBuf, json: bool, options: ScrubOptions) {
             std::process::exit(2);
         }
     };
-    let (sanitized, summary) = scrub_text_with_options(&input, options);
-    println!("{}", sanitized);
+    let name = path.display().to_string();
+    let (sanitized, summary, findings) = scrub_input(&name, &input, options, review);
+    if summary.blocked == 0 {
+        println!("{}", sanitized);
+    }
     if json {
-        eprintln!("{}", json_report(&summary));
+        report.input(&name, Source::File, &input, &summary, &findings);
+        eprintln!("{}", report.finish().to_json());
+    }
+    if let Some(mut stream) = ndjson {
+        let written = stream.input(&name, Source::File, &input, None, &summary, &findings);
+        ndjson_written(written.and_then(|_| stream.finish()));
+    }
+    if summary.blocked > 0 {
+        report_block(options, &findings);
+        std::process::exit(BLOCKED_EXIT);
     }
+    check_fail_on(&summary, fail_on);
 }
 
-fn output_report(json: bool, summary: &Summary, extra: Option<&str>) {
-    if json {
-        let mut out = json_report(summary);
-        if let Some(e) = extra {
-            out.push_str(e);
+/// Prints what scrubbing `inputs` would change, leaving the clipboard and
+/// files alone. Exit statuses are those of a real run.
+fn run_dry_run(
+    inputs: Vec<CheckInput>,
+    preview_options: &PreviewOptions,
+    setup: &Setup,
+    fail_on: Option<Severity>,
+    mut ndjson: Option<Ndjson>,
+) {
+    let options = &setup.options;
+    let mut report = ReportBuilder::new(options);
+    let mut blocking: Vec<Finding> = Vec::new();
+    let mut total = Summary::default();
+    let (mut read_failed, mut scanned, mut skipped) = (false, 0usize, 0usize);
+    for input in inputs {
+        let text = match input.content {
+            Content::Text(s) => s,
+            Content::Skipped(why) => {
+                match ndjson.as_mut() {
+                    Some(stream) => ndjson_written(stream.skipped(&input.name, &why.to_string())),
+                    None => eprintln!("Skipped {}: {}", input.name, why),
+                }
+                report.skipped(&input.name, &why.to_string());
+                skipped += 1;
+                continue;
+            }
+            Content::Failed(e) => {
+                eprintln!("Scrubby error: {}", e);
+                read_failed = true;
+                continue;
+            }
+        };
+        let (_, summary, findings) = scrub_text_with_findings(&text, options);
+        print!(
+            "{}",
+            preview(&input.name, &text, &findings, preview_options)
+        );
+        if let Some(stream) = ndjson.as_mut() {
+            let (name, source) = (&input.name, input.source);
+            ndjson_written(stream.input(name, source, &text, None, &summary, &findings));
+        } else if setup.json {
+            report.input(&input.name, input.source, &text, &summary, &findings);
+        } else {
+            eprintln!("{}: {}", input.name, file_summary(&summary));
         }
-        println!("{}", out);
+        blocking.extend(findings.into_iter().filter(|f| f.action == Action::Block));
+        total.add(&summary);
+        scanned += 1;
+    }
+    if let Some(stream) = ndjson {
+        ndjson_written(stream.finish());
+    } else if setup.json {
+        eprintln!("{}", report.finish().to_json());
     } else {
-        println!("{}", format_summary(summary));
+        if scanned > 1 || skipped > 0 {
+            eprintln!(
+                "Total: {}{}: {}",
+                count_noun(scanned, "input"),
+                skipped_note(skipped),
+                file_summary(&total)
+            );
+        }
+        eprintln!("Dry run: nothing was written.");
+    }
+    if read_failed {
+        std::process::exit(2);
+    }
+    if total.blocked > 0 {
+        report_block(options, &blocking);
+        std::process::exit(BLOCKED_EXIT);
+    }
+    check_fail_on(&total, fail_on);
+}
+
+/// Scrubs several files, or the files under directories. Each file's text is
+/// printed after a `==> path <==` header, with a line per file and a total
+/// on stderr. A blocking finding in any file suppresses all output.
+fn run_files(
+    paths: &[PathBuf],
+    max_file_size: u64,
+    dest: Destination,
+    setup: &Setup,
+    fail_on: Option<Severity>,
+    review: &mut Option<Review>,
+    mut ndjson: Option<Ndjson>,
+) {
+    let options = &setup.options;
+    // Files already in the mirror directory are its own output, not input.
+    let out_dir = match &dest {
+        Destination::Mirror(dir) => std::fs::canonicalize(dir).ok(),
+        _ => None,
+    };
+    // Nor are backups, from this run or an earlier one.
+    let backup_suffixes: Vec<&str> = match &dest {
+        Destination::InPlace(suffix) => [Some(DEFAULT_BACKUP_SUFFIX), suffix.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        _ => Vec::new(),
+    };
+    let mut outputs: Vec<Output> = Vec::new();
+    let mut report = ReportBuilder::new(options);
+    let mut blocking: Vec<Finding> = Vec::new();
+    let mut total = Summary::default();
+    let (mut read_failed, mut skipped) = (false, 0usize);
+    for root in paths {
+        for input in file_inputs(root, max_file_size) {
+            let inside_out_dir = out_dir.as_ref().is_some_and(|dir| {
+                input
+                    .path
+                    .as_ref()
+                    .and_then(|p| std::fs::canonicalize(p).ok())
+                    .is_some_and(|p| p.starts_with(dir))
+            });
+            let backup = input.path.as_deref().is_some_and(|p| {
+                p != root.as_path() && backup_suffixes.iter().any(|s| is_backup(p, s))
+            });
+            if inside_out_dir || backup {
+                continue;
+            }
+            let text = match input.content {
+                Content::Text(s) => s,
+                Content::Skipped(why) => {
+                    match ndjson.as_mut() {
+                        Some(stream) => {
+                            ndjson_written(stream.skipped(&input.name, &why.to_string()))
+                        }
+                        None => eprintln!("Skipped {}: {}", input.name, why),
+                    }
+                    report.skipped(&input.name, &why.to_string());
+                    skipped += 1;
+                    continue;
+                }
+                Content::Failed(e) => {
+                    eprintln!("Scrubby error: {}", e);
+                    read_failed = true;
+                    continue;
+                }
+            };
+            let (sanitized, summary, findings) = scrub_input(&input.name, &text, options, review);
+            if let Some(stream) = ndjson.as_mut() {
+                let name = &input.name;
+                ndjson_written(stream.input(name, Source::File, &text, None, &summary, &findings));
+            } else if setup.json {
+                report.input(&input.name, Source::File, &text, &summary, &findings);
+            } else {
+                eprintln!("{}: {}", input.name, file_summary(&summary));
+            }
+            blocking.extend(findings.into_iter().filter(|f| f.action == Action::Block));
+            total.add(&summary);
+            outputs.push(Output {
+                name: input.name,
+                path: input.path.unwrap_or_default(),
+                root: root.clone(),
+                changed: sanitized != text,
+                sanitized,
+            });
+        }
+    }
+
+    if let Destination::Mirror(dir) = &dest {
+        let files = outputs.iter().map(|o| (o.root.as_path(), o.path.as_path()));
+        if let Some((first, second, target)) = mirror_collision(dir, files) {
+            eprintln!(
+                "Scrubby error: {} and {} would both be written to {}",
+                first.display(),
+                second.display(),
+                target.display()
+            );
+            std::process::exit(1);
+        }
+    }
+    let mut write_failed = false;
+    if total.blocked == 0 {
+        write_failed = !write_outputs(&outputs, &dest);
+    }
+    if let Some(stream) = ndjson {
+        ndjson_written(stream.finish());
+    } else if setup.json {
+        eprintln!("{}", report.finish().to_json());
+    } else {
+        eprintln!(
+            "Total: {}{}: {}",
+            count_noun(outputs.len(), "file"),
+            skipped_note(skipped),
+            file_summary(&total)
+        );
+    }
+    if read_failed {
+        std::process::exit(2);
+    }
+    if total.blocked > 0 {
+        report_block(options, &blocking);
+        if !matches!(dest, Destination::Stdout) {
+            eprintln!("No files were written.");
+        }
+        std::process::exit(BLOCKED_EXIT);
+    }
+    if write_failed {
+        std::process::exit(3);
+    }
+    check_fail_on(&total, fail_on);
+}
+
+/// `--ndjson` records, on stdout or stderr.
+type Ndjson = NdjsonWriter<Box<dyn Write>>;
+
+fn open_ndjson(stream: Stream) -> Ndjson {
+    match stream {
+        Stream::Stdout => NdjsonWriter::new(Box::new(io::stdout())),
+        Stream::Stderr => NdjsonWriter::new(Box::new(io::stderr())),
+    }
+}
+
+/// Exits with 3 if a record could not be written.
+fn ndjson_written<T>(result: io::Result<T>) -> T {
+    result.unwrap_or_else(|e| {
+        eprintln!("Scrubby error: Failed to write NDJSON: {}", e);
+        std::process::exit(3);
+    })
+}
+
+/// A scanned file and its sanitized text.
+struct Output {
+    name: String,
+    path: PathBuf,
+    /// The argument the file was found under.
+    root: PathBuf,
+    changed: bool,
+    sanitized: String,
+}
+
+/// Prints or writes the sanitized files; false if any write failed.
+fn write_outputs(outputs: &[Output], dest: &Destination) -> bool {
+    let mut ok = true;
+    let mut written = 0;
+    for output in outputs {
+        let result = match dest {
+            Destination::Stdout => {
+                println!("==> {} <==", output.name);
+                print!("{}", output.sanitized);
+                if !output.sanitized.is_empty() && !output.sanitized.ends_with('\n') {
+                    println!();
+                }
+                continue;
+            }
+            Destination::InPlace(_) if !output.changed => continue,
+            Destination::InPlace(suffix) => suffix
+                .as_deref()
+                .map_or(Ok(()), |suffix| backup(&output.path, suffix).map(|_| ()))
+                .and_then(|_| write_atomic(&output.path, &output.sanitized)),
+            Destination::Mirror(dir) => {
+                let target = mirror_path(dir, &output.root, &output.path);
+                write_atomic(&target, &output.sanitized)
+            }
+        };
+        match result {
+            Ok(()) => written += 1,
+            Err(e) => {
+                eprintln!("Scrubby error: Failed to write {}: {}", output.name, e);
+                ok = false;
+            }
+        }
+    }
+    match dest {
+        Destination::Stdout => {}
+        Destination::InPlace(_) => eprintln!("Rewrote {}.", count_noun(written, "file")),
+        Destination::Mirror(dir) => eprintln!(
+            "Wrote {} to {}.",
+            count_noun(written, "file"),
+            dir.display()
+        ),
+    }
+    ok
+}
+
+/// One-line counts, e.g. `3 redacted (2 emails, 1 JWT), 1 kept`.
+fn file_summary(summary: &Summary) -> String {
+    let kinds: Vec<String> = [
+        (summary.emails, "email"),
+        (summary.ips, "IP"),
+        (summary.uuids, "UUID"),
+        (summary.jwts, "JWT"),
+        (summary.tokens, "token"),
+        (summary.terms, "term"),
+        (summary.custom, "custom"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|&(n, kind)| count_noun(n, kind))
+    .collect();
+    let mut out = format!("{} redacted", summary.total());
+    if !kinds.is_empty() {
+        out.push_str(&format!(" ({})", kinds.join(", ")));
+    }
+    let kept = summary.allowlisted + summary.allowed + summary.warned;
+    if kept > 0 {
+        out.push_str(&format!(", {} kept", kept));
+    }
+    out
+}
+
+/// Exit status when a `block` policy stopped the output.
+const BLOCKED_EXIT: i32 = 4;
+
+fn report_block(options: &ScrubOptions, findings: &[Finding]) {
+    for (key, count) in options.policy.blocked_by(findings) {
+        eprintln!(
+            "Scrubby blocked: policy '{} = block' matched {} finding(s)",
+            key, count
+        );
     }
 }
 
-fn json_report(summary: &Summary) -> String {
-    format!(
-        "{{\"emails\":{},\"ips\":{},\"uuids\":{},\"jwts\":{},\"tokens\":{},\"safe_to_paste\":true}}",
-        summary.emails, summary.ips, summary.uuids, summary.jwts, summary.tokens
-    )
+/// Exit status when a finding reached the `--fail-on` severity.
+const FAIL_ON_EXIT: i32 = 5;
+
+fn check_fail_on(summary: &Summary, fail_on: Option<Severity>) {
+    let (Some(threshold), Some(max)) = (fail_on, summary.max_severity()) else {
+        return;
+    };
+    if max >= threshold {
+        let count: usize = Severity::ALL
+            .iter()
+            .filter(|&&s| s >= threshold)
+            .map(|&s| summary.severities[s as usize])
+            .sum();
+        eprintln!(
+            "Scrubby: {} finding(s) at or above {} severity (highest: {})",
+            count, threshold, max
+        );
+        std::process::exit(FAIL_ON_EXIT);
+    }
+}
+
+/// Clears the clipboard if the policy asks for it after a block.
+fn clipboard_on_block(options: &ScrubOptions) {
+    if !options.policy.clear_on_block {
+        eprintln!("Clipboard left unchanged.");
+        return;
+    }
+    if let Err(e) = write_clipboard("") {
+        eprintln!("Scrubby error: {}", e);
+        std::process::exit(3);
+    }
+    eprintln!("Clipboard cleared.");
+}
+
+fn print_effective_config(layers: &[Layer]) {
+    let entries: Vec<(String, String)> = effective_entries(layers)
+        .into_iter()
+        .map(|(key, value, source)| (format!("{}={}", key, value), source.to_string()))
+        .collect();
+    let width = entries.iter().map(|(e, _)| e.len()).max().unwrap_or(0);
+    for (entry, source) in entries {
+        println!("{:<width$}  # {}", entry, source, width = width);
+    }
+}
+
+/// Prints the summary, or with `--json` the report on the one input, or
+/// with `--ndjson` its records.
+fn output_report(
+    json: bool,
+    mut report: ReportBuilder,
+    ndjson: Option<Ndjson>,
+    (source, name, text): (Source, &str, &str),
+    summary: &Summary,
+    findings: &[Finding],
+) {
+    if let Some(mut stream) = ndjson {
+        let written = stream.input(name, source, text, None, summary, findings);
+        ndjson_written(written.and_then(|_| stream.finish()));
+    } else if json {
+        report.input(name, source, text, summary, findings);
+        println!("{}", report.finish().to_json());
+    } else {
+        println!("{}", format_summary(summary));
+    }
 }
 
 fn apply_feature_gates(
@@ -290,7 +1396,7 @@ fn apply_feature_gates(
     if config {
         #[cfg(not(feature = "pro-config"))]
         {
-            eprintln!("Scrubby error: --config is a Pro feature (build with feature pro-config)");
+            eprintln!("Scrubby error: --config/--profile is a Pro feature (build with feature pro-config)");
             std::process::exit(1);
         }
     }
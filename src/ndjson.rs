@@ -0,0 +1,168 @@
+//! Newline-delimited JSON records of findings, written as each input is
+//! scanned rather than in one report at the end.
+//!
+//! Every line is one record with a `type`: a `finding` per finding (the
+//! fields of a report finding, plus the input it is in), a `skipped` per
+//! input that was not scanned, and a closing `summary` with the totals.
+
+use std::io::{self, Write};
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::location::LineIndex;
+use crate::redactor::Finding;
+use crate::report::{FindingReport, Source, SummaryReport, SCHEMA_VERSION};
+use crate::Summary;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum Record<'a> {
+    Finding {
+        input: &'a str,
+        source: Source,
+        #[serde(flatten)]
+        finding: FindingReport,
+    },
+    Skipped {
+        input: &'a str,
+        reason: &'a str,
+    },
+    Summary {
+        schema_version: u32,
+        /// Inputs scanned.
+        inputs: usize,
+        skipped: usize,
+        duration_ms: f64,
+        #[serde(flatten)]
+        summary: SummaryReport,
+    },
+}
+
+/// Writes records to `out`, flushing after each one.
+pub struct NdjsonWriter<W: Write> {
+    out: W,
+    clock: Instant,
+    total: Summary,
+    inputs: usize,
+    skipped: usize,
+}
+
+impl<W: Write> NdjsonWriter<W> {
+    pub fn new(out: W) -> NdjsonWriter<W> {
+        NdjsonWriter {
+            out,
+            clock: Instant::now(),
+            total: Summary::default(),
+            inputs: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Writes a record per finding in `text`. `line_numbers`, when the text
+    /// is not a whole file, gives the file's line number of each line.
+    pub fn input(
+        &mut self,
+        name: &str,
+        source: Source,
+        text: &str,
+        line_numbers: Option<&[usize]>,
+        summary: &Summary,
+        findings: &[Finding],
+    ) -> io::Result<()> {
+        let index = LineIndex::new(text);
+        for f in findings {
+            let mut finding = FindingReport::new(text, &index, f);
+            if let Some(numbers) = line_numbers {
+                finding.span.line = numbers[finding.span.line - 1];
+                finding.span.end_line = numbers[finding.span.end_line - 1];
+            }
+            self.write(&Record::Finding {
+                input: name,
+                source,
+                finding,
+            })?;
+        }
+        self.total.add(summary);
+        self.inputs += 1;
+        Ok(())
+    }
+
+    pub fn skipped(&mut self, name: &str, reason: &str) -> io::Result<()> {
+        self.skipped += 1;
+        self.write(&Record::Skipped {
+            input: name,
+            reason,
+        })
+    }
+
+    /// Writes the summary record and returns the stream.
+    pub fn finish(mut self) -> io::Result<W> {
+        let record = Record::Summary {
+            schema_version: SCHEMA_VERSION,
+            inputs: self.inputs,
+            skipped: self.skipped,
+            duration_ms: self.clock.elapsed().as_secs_f64() * 1000.0,
+            summary: (&self.total).into(),
+        };
+        self.write(&record)?;
+        Ok(self.out)
+    }
+
+    fn write(&mut self, record: &Record) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, record)?;
+        self.out.write_all(b"\n")?;
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scrub_text_with_findings, ScrubOptions};
+
+    #[test]
+    fn writes_a_line_per_finding_then_a_summary() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        let text = "mail a@b.io\nfrom 10.0.0.1\n";
+        let (_, summary, findings) = scrub_text_with_findings(text, &ScrubOptions::default());
+        writer
+            .input(
+                "notes",
+                Source::File,
+                text,
+                Some(&[7, 9]),
+                &summary,
+                &findings,
+            )
+            .unwrap();
+        writer.skipped("logo.png", "binary file").unwrap();
+        let out = String::from_utf8(writer.finish().unwrap()).unwrap();
+        let records: Vec<serde_json::Value> = out
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0]["type"], "finding");
+        assert_eq!(records[0]["input"], "notes");
+        assert_eq!(records[0]["kind"], "email");
+        assert_eq!(records[0]["span"]["line"], 7);
+        assert_eq!(records[1]["span"]["line"], 9);
+        assert_eq!(records[1]["placeholder"], "<IP>");
+        assert_eq!(
+            records[2],
+            serde_json::json!({"type": "skipped", "input": "logo.png", "reason": "binary file"})
+        );
+        assert_eq!(records[3]["type"], "summary");
+        assert_eq!(
+            (
+                records[3]["inputs"].as_u64(),
+                records[3]["skipped"].as_u64()
+            ),
+            (Some(1), Some(1))
+        );
+        assert_eq!(records[3]["emails"], 1);
+        assert_eq!(records[3]["safe_to_paste"], true);
+    }
+}
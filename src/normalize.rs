@@ -0,0 +1,209 @@
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::UnicodeNormalization;
+
+/// Text prepared for detection: NFKC-normalized, zero-width characters
+/// stripped and common homoglyphs folded to ASCII. Every byte of `text`
+/// remembers which characters of the original input produced it, so spans
+/// found in `text` can be mapped back onto the original bytes.
+#[derive(Debug, Clone)]
+pub struct Normalized {
+    pub text: String,
+    // (start, end) byte range in the original input for each byte of `text`.
+    // Empty when the input was already normalized (identity mapping).
+    origin: Vec<(usize, usize)>,
+}
+
+impl Normalized {
+    pub fn to_original(&self, start: usize, end: usize) -> (usize, usize) {
+        if self.origin.is_empty() || start >= end {
+            return (start, end);
+        }
+        (self.origin[start].0, self.origin[end - 1].1)
+    }
+}
+
+pub fn normalize(input: &str) -> Normalized {
+    normalize_skipping(input, &[])
+}
+
+/// Like [`normalize`], but also drops the sorted, non-overlapping byte ranges
+/// in `skip` so matches can run across them.
+pub fn normalize_skipping(input: &str, skip: &[(usize, usize)]) -> Normalized {
+    if skip.is_empty() && input.is_ascii() {
+        return Normalized {
+            text: input.to_string(),
+            origin: Vec::new(),
+        };
+    }
+
+    let mut out = Normalized {
+        text: String::with_capacity(input.len()),
+        origin: Vec::with_capacity(input.len()),
+    };
+    let mut run = Vec::new();
+    let mut skip = skip.iter().peekable();
+    for (start, c) in input.char_indices() {
+        while skip.peek().is_some_and(|&&(_, e)| e <= start) {
+            skip.next();
+        }
+        if skip.peek().is_some_and(|&&(s, _)| s <= start) || is_zero_width(c) {
+            continue;
+        }
+        if c.is_ascii() {
+            out.push_run(&run);
+            run.clear();
+        }
+        run.push((start, c));
+    }
+    out.push_run(&run);
+    out
+}
+
+impl Normalized {
+    /// Appends the NFKC form of `run`: an ASCII character, if any, and the
+    /// non-ASCII characters after it, so that combining marks compose with
+    /// their base. Each segment of a starter and its combining marks maps
+    /// back to its own characters, unless composition crosses segments; then
+    /// the whole run maps back as one.
+    fn push_run(&mut self, run: &[(usize, char)]) {
+        let (Some(&(first, _)), Some(&(last, c))) = (run.first(), run.last()) else {
+            return;
+        };
+        if run.len() == 1 && c.is_ascii() {
+            self.text.push(c);
+            self.origin.push((first, first + 1));
+            return;
+        }
+        let mut segments: Vec<(usize, usize, String)> = Vec::new();
+        for &(start, c) in run {
+            let end = start + c.len_utf8();
+            match segments.last_mut() {
+                Some(segment) if canonical_combining_class(c) != 0 => {
+                    segment.1 = end;
+                    segment.2.push(c);
+                }
+                _ => segments.push((start, end, c.to_string())),
+            }
+        }
+        for segment in segments.iter_mut() {
+            segment.2 = segment.2.nfkc().collect();
+        }
+        let whole: String = run.iter().map(|&(_, c)| c).nfkc().collect();
+        if segments.iter().map(|s| s.2.as_str()).collect::<String>() != whole {
+            segments = vec![(first, last + c.len_utf8(), whole)];
+        }
+        for (start, end, normalized) in segments {
+            for n in normalized.chars().map(fold_confusable) {
+                self.text.push(n);
+                self.origin
+                    .extend(std::iter::repeat_n((start, end), n.len_utf8()));
+            }
+        }
+    }
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' | '\u{180E}' | '\u{200B}' | '\u{200C}' | '\u{200D}' | '\u{2060}' | '\u{FEFF}'
+    )
+}
+
+fn fold_confusable(c: char) -> char {
+    match c {
+        // Cyrillic
+        'а' => 'a',
+        'е' => 'e',
+        'о' => 'o',
+        'р' => 'p',
+        'с' => 'c',
+        'у' => 'y',
+        'х' => 'x',
+        'і' => 'i',
+        'ј' => 'j',
+        'ѕ' => 's',
+        'ԁ' => 'd',
+        'А' => 'A',
+        'В' => 'B',
+        'Е' => 'E',
+        'К' => 'K',
+        'М' => 'M',
+        'Н' => 'H',
+        'О' => 'O',
+        'Р' => 'P',
+        'С' => 'C',
+        'Т' => 'T',
+        'Х' => 'X',
+        'У' => 'Y',
+        'І' => 'I',
+        'Ј' => 'J',
+        'Ѕ' => 'S',
+        // Greek
+        'Α' => 'A',
+        'Β' => 'B',
+        'Ε' => 'E',
+        'Ζ' => 'Z',
+        'Η' => 'H',
+        'Ι' => 'I',
+        'Κ' => 'K',
+        'Μ' => 'M',
+        'Ν' => 'N',
+        'Ο' => 'O',
+        'Ρ' => 'P',
+        'Τ' => 'T',
+        'Υ' => 'Y',
+        'Χ' => 'X',
+        'ο' => 'o',
+        'ν' => 'v',
+        'ι' => 'i',
+        // Punctuation
+        '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2212}' => '-',
+        '\u{3002}' | '\u{FF61}' => '.',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_is_identity() {
+        let n = normalize("plain text");
+        assert_eq!(n.text, "plain text");
+        assert_eq!(n.to_original(0, 5), (0, 5));
+    }
+
+    #[test]
+    fn folds_fullwidth_and_homoglyphs() {
+        let n = normalize("аdmin＠example.com");
+        assert_eq!(n.text, "admin@example.com");
+    }
+
+    #[test]
+    fn composes_combining_marks_with_their_base() {
+        let input = "cafe\u{301} ｘ";
+        let n = normalize(input);
+        assert_eq!(n.text, "café x");
+        assert_eq!(n.to_original(3, 5), (3, 6));
+        assert_eq!(n.to_original(6, 7), (7, input.len()));
+        assert_eq!(normalize("\u{1100}\u{1161}").text, "\u{AC00}");
+    }
+
+    #[test]
+    fn maps_spans_across_stripped_zero_width() {
+        let input = "x ab\u{200B}cd y";
+        let n = normalize(input);
+        assert_eq!(n.text, "x abcd y");
+        let (s, e) = n.to_original(2, 6);
+        assert_eq!(&input[s..e], "ab\u{200B}cd");
+    }
+
+    #[test]
+    fn skipped_ranges_are_joined() {
+        let input = "ab\\\n  cd";
+        let n = normalize_skipping(input, &[(2, 6)]);
+        assert_eq!(n.text, "abcd");
+        assert_eq!(n.to_original(0, 4), (0, input.len()));
+    }
+}
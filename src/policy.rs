@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use crate::redactor::Finding;
+
+/// What happens to a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Action {
+    /// Replace it with a placeholder.
+    #[default]
+    Redact,
+    /// Leave it in place but report it.
+    Warn,
+    /// Refuse to produce output at all.
+    Block,
+    /// Leave it in place, like an allowlisted value.
+    Allow,
+}
+
+impl Action {
+    pub const NAMES: [&'static str; 4] = ["redact", "warn", "block", "allow"];
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        match name {
+            "redact" => Some(Action::Redact),
+            "warn" => Some(Action::Warn),
+            "block" => Some(Action::Block),
+            "allow" => Some(Action::Allow),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Redact => "redact",
+            Action::Warn => "warn",
+            Action::Block => "block",
+            Action::Allow => "allow",
+        }
+    }
+}
+
+/// Per-kind actions. Anything without an entry is redacted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Policy {
+    actions: BTreeMap<String, Action>,
+    /// Clear the clipboard instead of leaving it untouched when blocking.
+    pub clear_on_block: bool,
+}
+
+impl Policy {
+    /// Sets the action for a kind or for `rule.<name>`.
+    pub fn set(&mut self, key: &str, action: Action) {
+        self.actions.insert(key.to_string(), action);
+    }
+
+    /// Every key with an explicit action, in key order.
+    pub fn actions(&self) -> impl Iterator<Item = (&str, Action)> + '_ {
+        self.actions.iter().map(|(k, &a)| (k.as_str(), a))
+    }
+
+    /// The action for a finding of `kind`, produced by custom rule `rule` if
+    /// any, and the policy key it came from. A rule's own entry wins over the
+    /// `custom` entry.
+    pub fn action(&self, kind: &str, rule: Option<&str>) -> (Action, String) {
+        if let Some(name) = rule {
+            let key = format!("rule.{}", name);
+            if let Some(&action) = self.actions.get(&key) {
+                return (action, key);
+            }
+        }
+        let action = self.actions.get(kind).copied().unwrap_or_default();
+        (action, kind.to_string())
+    }
+
+    /// `(policy key, number of findings)` for every blocking finding.
+    pub fn blocked_by(&self, findings: &[Finding]) -> Vec<(String, usize)> {
+        let mut fired: Vec<(String, usize)> = Vec::new();
+        for f in findings.iter().filter(|f| f.action == Action::Block) {
+            let (_, key) = self.action(f.kind, f.rule.as_deref());
+            match fired.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, n)) => *n += 1,
+                None => fired.push((key, 1)),
+            }
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rule_entry_wins_over_custom() {
+        let mut p = Policy::default();
+        p.set("custom", Action::Warn);
+        p.set("rule.private-key", Action::Block);
+        p.set("jwt", Action::Block);
+        assert_eq!(p.action("email", None).0, Action::Redact);
+        assert_eq!(p.action("custom", Some("ticket")).0, Action::Warn);
+        assert_eq!(
+            p.action("custom", Some("private-key")),
+            (Action::Block, "rule.private-key".to_string())
+        );
+        assert_eq!(p.action("jwt", None), (Action::Block, "jwt".to_string()));
+    }
+}
@@ -0,0 +1,324 @@
+//! A diff of what scrubbing would change, for `scrubby scrub --dry-run`.
+
+use crate::location::LineIndex;
+use crate::redactor::Finding;
+
+/// How [`preview`] lays out the changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiffStyle {
+    /// `-`/`+` lines in hunks with context, like `diff -u`.
+    #[default]
+    Unified,
+    /// Only the changed lines, each redaction as `[original → placeholder]`.
+    Inline,
+}
+
+impl DiffStyle {
+    pub const NAMES: [&'static str; 2] = ["unified", "inline"];
+
+    pub fn from_name(name: &str) -> Option<DiffStyle> {
+        match name {
+            "unified" => Some(DiffStyle::Unified),
+            "inline" => Some(DiffStyle::Inline),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreviewOptions {
+    pub style: DiffStyle,
+    /// Show only the first two characters of each original value.
+    pub mask: bool,
+    /// Use ANSI colors.
+    pub color: bool,
+}
+
+/// Unchanged lines shown around each change in a unified diff.
+const CONTEXT: usize = 3;
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const CYAN: &str = "\x1b[36m";
+const BOLD: &str = "\x1b[1m";
+const NORMAL: &str = "\x1b[22m";
+const RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    Same,
+    Old,
+    New,
+}
+
+type Piece = (String, Mark);
+
+/// Lines `first..=last` of the original that redactions touch, split into
+/// pieces before (`old`), after (`new`) and side by side (`inline`).
+struct Run {
+    first: usize,
+    last: usize,
+    old: Vec<Vec<Piece>>,
+    new: Vec<Vec<Piece>>,
+    inline: Vec<Vec<Piece>>,
+}
+
+/// The changes that `findings` make to `text`, labelled `name`. Findings left
+/// in place are not changes; without any change the preview is empty.
+pub fn preview(name: &str, text: &str, findings: &[Finding], options: &PreviewOptions) -> String {
+    let index = LineIndex::new(text);
+    let runs = runs(text, &index, findings, options.mask);
+    if runs.is_empty() {
+        return String::new();
+    }
+    match options.style {
+        DiffStyle::Unified => unified(name, text, &index, &runs, options.color),
+        DiffStyle::Inline => inline(name, &runs, options.color),
+    }
+}
+
+fn runs(text: &str, index: &LineIndex, findings: &[Finding], mask: bool) -> Vec<Run> {
+    // Findings on the same or neighbouring lines form one run.
+    let mut groups: Vec<(usize, usize, Vec<&Finding>)> = Vec::new();
+    for f in findings.iter().filter(|f| !f.placeholder.is_empty()) {
+        let (first, _) = index.locate(f.start);
+        let (last, _) = index.locate(f.end);
+        match groups.last_mut() {
+            Some(group) if first <= group.1 + 1 => {
+                group.1 = group.1.max(last);
+                group.2.push(f);
+            }
+            _ => groups.push((first, last, vec![f])),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(first, last, findings)| {
+            let (mut pos, _) = index.line_span(first);
+            let (_, end) = index.line_span(last);
+            let (mut old, mut new, mut inline) = (Vec::new(), Vec::new(), Vec::new());
+            for f in findings {
+                let same = (text[pos..f.start].to_string(), Mark::Same);
+                let value = match mask {
+                    true => masked(&text[f.start..f.end]),
+                    false => text[f.start..f.end].to_string(),
+                };
+                let placeholder = (f.placeholder.clone(), Mark::New);
+                let separators = (f.separators.clone(), Mark::Same);
+                old.extend([same.clone(), (value.clone(), Mark::Old)]);
+                new.extend([same.clone(), placeholder.clone(), separators.clone()]);
+                inline.extend([
+                    same,
+                    ("[".to_string(), Mark::Same),
+                    (value, Mark::Old),
+                    (" → ".to_string(), Mark::Same),
+                    placeholder,
+                    ("]".to_string(), Mark::Same),
+                    separators,
+                ]);
+                pos = f.end;
+            }
+            let rest = (text[pos..end].to_string(), Mark::Same);
+            old.push(rest.clone());
+            new.push(rest.clone());
+            inline.push(rest);
+            Run {
+                first,
+                last,
+                old: split_lines(old),
+                new: split_lines(new),
+                inline: split_lines(inline),
+            }
+        })
+        .collect()
+}
+
+/// Keeps the first two characters of `value` and stars out the rest, apart
+/// from line breaks.
+fn masked(value: &str) -> String {
+    value
+        .chars()
+        .enumerate()
+        .map(|(i, c)| if i < 2 || c == '\n' { c } else { '*' })
+        .collect()
+}
+
+fn split_lines(pieces: Vec<Piece>) -> Vec<Vec<Piece>> {
+    let mut lines = vec![Vec::new()];
+    for (text, mark) in pieces {
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+            }
+            if !part.is_empty() {
+                lines.last_mut().unwrap().push((part.to_string(), mark));
+            }
+        }
+    }
+    for line in lines.iter_mut() {
+        if let Some((text, _)) = line.last_mut() {
+            if text.ends_with('\r') {
+                text.pop();
+            }
+        }
+    }
+    lines
+}
+
+fn unified(name: &str, text: &str, index: &LineIndex, runs: &[Run], color: bool) -> String {
+    // A final line break does not start another line.
+    let line_count = (index.line_count() - text.ends_with('\n') as usize).max(1);
+    let paint = |style: &str, s: String| match color {
+        true => format!("{}{}{}\n", style, s, RESET),
+        false => format!("{}\n", s),
+    };
+    let mut out = String::new();
+    out.push_str(&paint(BOLD, format!("--- {}", name)));
+    out.push_str(&paint(BOLD, format!("+++ {} (scrubbed)", name)));
+    let mut removed_before = 0;
+    let mut i = 0;
+    while i < runs.len() {
+        let mut j = i + 1;
+        while j < runs.len() && runs[j].first - runs[j - 1].last - 1 <= 2 * CONTEXT {
+            j += 1;
+        }
+        let hunk = &runs[i..j];
+        let old_start = hunk[0].first.saturating_sub(CONTEXT).max(1);
+        let old_end = (hunk[j - i - 1].last + CONTEXT).min(line_count);
+        let removed: usize = hunk.iter().map(|r| r.old.len() - r.new.len()).sum();
+        let old_len = old_end - old_start + 1;
+        out.push_str(&paint(
+            CYAN,
+            format!(
+                "@@ -{},{} +{},{} @@",
+                old_start,
+                old_len,
+                old_start - removed_before,
+                old_len - removed
+            ),
+        ));
+        let mut next = old_start;
+        for run in hunk {
+            for n in next..run.first {
+                out.push_str(&format!(" {}\n", index.line(n)));
+            }
+            for line in run.old.iter() {
+                out.push_str(&render("-", Some(RED), line, color));
+            }
+            for line in run.new.iter() {
+                out.push_str(&render("+", Some(GREEN), line, color));
+            }
+            next = run.last + 1;
+        }
+        for n in next..=old_end {
+            out.push_str(&format!(" {}\n", index.line(n)));
+        }
+        removed_before += removed;
+        i = j;
+    }
+    out
+}
+
+fn inline(name: &str, runs: &[Run], color: bool) -> String {
+    let width = runs.last().map_or(1, |r| r.last.to_string().len());
+    let mut out = match color {
+        true => format!("{}{}{}\n", BOLD, name, RESET),
+        false => format!("{}\n", name),
+    };
+    for run in runs {
+        for (k, line) in run.inline.iter().enumerate() {
+            let prefix = format!("{:>width$}: ", run.first + k, width = width);
+            out.push_str(&render(&prefix, None, line, color));
+        }
+    }
+    out
+}
+
+/// `prefix` and the pieces of one line. Inside a `line_color` line the
+/// changed pieces are bold; otherwise old pieces are red and new ones green.
+fn render(prefix: &str, line_color: Option<&str>, pieces: &[Piece], color: bool) -> String {
+    let mut out = String::new();
+    if let (true, Some(c)) = (color, line_color) {
+        out.push_str(c);
+    }
+    out.push_str(prefix);
+    for (text, mark) in pieces {
+        let style = match (line_color, mark) {
+            (_, Mark::Same) => None,
+            (Some(_), _) => Some((BOLD, NORMAL)),
+            (None, Mark::Old) => Some((RED, RESET)),
+            (None, Mark::New) => Some((GREEN, RESET)),
+        };
+        match style.filter(|_| color) {
+            Some((on, off)) => out.push_str(&format!("{}{}{}", on, text, off)),
+            None => out.push_str(text),
+        }
+    }
+    if color && line_color.is_some() {
+        out.push_str(RESET);
+    }
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{scrub_text_with_findings, ScrubOptions};
+
+    fn show(text: &str, options: PreviewOptions) -> String {
+        let (_, _, findings) = scrub_text_with_findings(text, &ScrubOptions::default());
+        preview("notes", text, &findings, &options)
+    }
+
+    #[test]
+    fn unified_diff_shows_changed_lines_with_context() {
+        let text = "a\nb\nc\nd\nmail a@b.io\ne\nf\ng\nh\ni\nj\nk\nl\nm\nalso c@d.io\n";
+        let out = show(text, PreviewOptions::default());
+        assert_eq!(
+            out,
+            "--- notes\n+++ notes (scrubbed)\n\
+             @@ -2,7 +2,7 @@\n b\n c\n d\n-mail a@b.io\n+mail <EMAIL>\n e\n f\n g\n\
+             @@ -12,4 +12,4 @@\n k\n l\n m\n-also c@d.io\n+also <EMAIL>\n"
+        );
+        assert_eq!(show("nothing here\n", PreviewOptions::default()), "");
+    }
+
+    #[test]
+    fn wrapped_token_keeps_its_line_break_like_the_output() {
+        let options = ScrubOptions {
+            reassemble: true,
+            ..ScrubOptions::default()
+        };
+        let text = "key AbCDeF0123456789Ab\nCDeF0123456789 end\n";
+        let (scrubbed, _, findings) = scrub_text_with_findings(text, &options);
+        assert_eq!(scrubbed, "key <TOKEN>\n end\n");
+        let out = preview("notes", text, &findings, &PreviewOptions::default());
+        assert_eq!(
+            out,
+            "--- notes\n+++ notes (scrubbed)\n\
+             @@ -1,2 +1,2 @@\n-key AbCDeF0123456789Ab\n-CDeF0123456789 end\n\
+             +key <TOKEN>\n+ end\n"
+        );
+    }
+
+    #[test]
+    fn inline_diff_can_mask_originals() {
+        let options = PreviewOptions {
+            style: DiffStyle::Inline,
+            mask: true,
+            color: false,
+        };
+        let out = show("hi\nmail a@b.io now\n", options);
+        assert_eq!(out, "notes\n2: mail [a@**** → <EMAIL>] now\n");
+
+        let colored = show(
+            "mail a@b.io\n",
+            PreviewOptions {
+                color: true,
+                ..options
+            },
+        );
+        assert!(colored.contains("\x1b[31ma@****\x1b[0m → \x1b[32m<EMAIL>\x1b[0m"));
+    }
+}
@@ -1,8 +1,8 @@
-use once_cell::sync::Lazy;
-use regex::Regex;
+use std::collections::HashMap;
 
-use crate::detectors::shannon_entropy;
 use crate::detectors::Detections;
+use crate::policy::Action;
+use crate::severity::Severity;
 use crate::ScrubOptions;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -12,155 +12,405 @@ pub struct RedactionCounts {
     pub uuids: usize,
     pub jwts: usize,
     pub tokens: usize,
+    pub terms: usize,
+    pub custom: usize,
+    /// Detected values left in place because the allowlist matched them.
+    pub allowlisted: usize,
+    /// Findings left in place because their policy is `allow`.
+    pub allowed: usize,
+    /// Findings left in place because their policy is `warn`.
+    pub warned: usize,
+    /// Findings whose policy is `block`; they are still redacted in `text`.
+    pub blocked: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// One detected span of the original input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    pub kind: &'static str,
+    pub start: usize,
+    pub end: usize,
+    /// Empty when the finding was left in place.
+    pub placeholder: String,
+    /// Line breaks of a reassembled value, written after the placeholder.
+    pub separators: String,
+    /// Confidence score, for heuristically detected tokens.
+    pub score: Option<f64>,
+    /// Name of the custom rule that matched, for `custom` findings.
+    pub rule: Option<String>,
+    /// What the policy did with it.
+    pub action: Action,
+    pub severity: Severity,
+}
+
+impl Finding {
+    /// Which pattern matched: `builtin.<kind>`, `rule.<name>` or `denylist`.
+    pub fn pattern_id(&self) -> String {
+        pattern_id(self.kind, self.rule.as_deref())
+    }
+}
+
+/// The id of the pattern behind findings of `kind` from `rule`.
+pub fn pattern_id(kind: &str, rule: Option<&str>) -> String {
+    match (rule, kind) {
+        (Some(name), _) => format!("rule.{}", name),
+        (None, "term") => "denylist".to_string(),
+        (None, kind) => format!("builtin.{}", kind),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct RedactionResult {
     pub text: String,
     pub counts: RedactionCounts,
+    pub findings: Vec<Finding>,
 }
 
-static EMAIL_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b").unwrap());
-
-static IPV4_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b").unwrap()
-});
-
-static UUID_V4_RE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(
-        r"\b[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-4[0-9a-fA-F]{3}-[89abAB][0-9a-fA-F]{3}-[0-9a-fA-F]{12}\b",
-    )
-    .unwrap()
-});
-
-static JWT_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\b[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\b").unwrap());
-
-static TOKEN_CANDIDATE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\b[A-Za-z0-9_-]{32,}\b").unwrap());
-
-pub fn redact(input: &str, _detections: &Detections, options: ScrubOptions) -> RedactionResult {
-    let mut counts = RedactionCounts::default();
-    let mut email_i = 0usize;
-    let mut ip_i = 0usize;
-    let mut uuid_i = 0usize;
-    let mut jwt_i = 0usize;
-    let mut token_i = 0usize;
-
-    let (text, emails) = replace_with_count(
-        input,
-        &EMAIL_RE,
-        "<EMAIL>",
-        options.stable_placeholders,
-        &mut email_i,
-    );
-    counts.emails += emails;
-
-    let (text, ips) = replace_with_count(
-        &text,
-        &IPV4_RE,
-        "<IP>",
-        options.stable_placeholders,
-        &mut ip_i,
-    );
-    counts.ips += ips;
-
-    let (text, uuids) = replace_with_count(
-        &text,
-        &UUID_V4_RE,
-        "<UUID>",
-        options.stable_placeholders,
-        &mut uuid_i,
-    );
-    counts.uuids += uuids;
-
-    let (text, jwts) = replace_with_count(
-        &text,
-        &JWT_RE,
-        "<JWT>",
-        options.stable_placeholders,
-        &mut jwt_i,
-    );
-    counts.jwts += jwts;
-
-    let (text, tokens) = replace_tokens(&text, options.stable_placeholders, &mut token_i);
-    counts.tokens += tokens;
+const LABELS: [&str; 7] = ["EMAIL", "IP", "UUID", "JWT", "TOKEN", "TERM", "CUSTOM"];
+/// Every kind of finding. Policies and severities can be set per kind;
+/// `custom` covers rules that do not set their own.
+pub const KINDS: [&str; 7] = ["email", "ip", "uuid", "jwt", "token", "term", "custom"];
+const TOKEN: usize = 4;
+const TERM: usize = 5;
+const CUSTOM: usize = 6;
+// Custom rules are the most specific, denylisted terms the least.
+const PRIORITY: [usize; 7] = [CUSTOM, 0, 1, 2, 3, TOKEN, TERM];
 
-    // TODO(pro-stable-placeholders): gate stable placeholders behind license checks.
-    RedactionResult { text, counts }
-}
+pub fn redact(input: &str, detections: &Detections, options: &ScrubOptions) -> RedactionResult {
+    // Earlier kinds win when spans overlap (an email's domain is not also a JWT).
+    let terms: Vec<(usize, usize)> = detections.terms.iter().map(|&(s, e, _)| (s, e)).collect();
+    let custom: Vec<(usize, usize)> = detections.custom.iter().map(|&(s, e, _)| (s, e)).collect();
+    let groups = [
+        &detections.emails,
+        &detections.ips,
+        &detections.uuids,
+        &detections.jwts,
+        &detections.tokens,
+        &terms,
+        &custom,
+    ];
+    let mut spans: Vec<(usize, usize, usize, usize)> = Vec::new();
+    for kind in PRIORITY {
+        spans = add_kind(spans, groups[kind], kind);
+    }
 
-fn replace_with_count(
-    input: &str,
-    re: &Regex,
-    replacement: &str,
-    stable: bool,
-    counter: &mut usize,
-) -> (String, usize) {
-    let mut count = 0usize;
+    let mut per_kind = [0usize; 7];
+    let mut per_label: HashMap<&str, usize> = HashMap::new();
+    let mut allowlisted = 0usize;
+    let (mut allowed, mut warned, mut blocked) = (0usize, 0usize, 0usize);
+    let mut findings = Vec::with_capacity(spans.len());
     let mut out = String::with_capacity(input.len());
     let mut last = 0usize;
-    for m in re.find_iter(input) {
-        out.push_str(&input[last..m.start()]);
-        if stable {
-            *counter += 1;
-            out.push_str(&format!(
-                "{}_{}",
-                replacement.trim_end_matches('>'),
-                *counter
-            ));
-            out.push('>');
-        } else {
-            out.push_str(replacement);
+    for (start, end, kind, i) in spans {
+        if options.kept.contains(&(start, end)) {
+            allowlisted += 1;
+            continue;
         }
-        count += 1;
-        last = m.end();
+        if !options.allowlist.is_empty()
+            && options
+                .allowlist
+                .allows(&span_value(input, start, end, &detections.gaps))
+        {
+            allowlisted += 1;
+            continue;
+        }
+        let (rule, severity) = match kind {
+            CUSTOM => {
+                let r = &options.rules[detections.custom[i].2];
+                let severity = r
+                    .severity
+                    .unwrap_or_else(|| options.severities.get("custom"));
+                (Some(r.name.clone()), severity)
+            }
+            _ => (None, options.severities.get(KINDS[kind])),
+        };
+        let score = match kind {
+            TOKEN => detections.token_scores.get(i).copied(),
+            _ => None,
+        };
+        let (action, _) = options.policy.action(KINDS[kind], rule.as_deref());
+        match action {
+            Action::Allow => {
+                allowed += 1;
+                continue;
+            }
+            Action::Warn => {
+                warned += 1;
+                findings.push(Finding {
+                    kind: KINDS[kind],
+                    start,
+                    end,
+                    placeholder: String::new(),
+                    separators: String::new(),
+                    score,
+                    rule,
+                    action,
+                    severity,
+                });
+                continue;
+            }
+            Action::Block => {
+                blocked += 1;
+                per_kind[kind] += 1;
+            }
+            Action::Redact => per_kind[kind] += 1,
+        }
+        out.push_str(&input[last..start]);
+        let label = match kind {
+            TERM => options.denylist.label(detections.terms[i].2),
+            CUSTOM => options.rules[detections.custom[i].2].label.as_str(),
+            _ => LABELS[kind],
+        };
+        let placeholder = if options.stable_placeholders {
+            let n = per_label.entry(label).or_default();
+            *n += 1;
+            format!("<{}_{}>", label, n)
+        } else {
+            format!("<{}>", label)
+        };
+        // Keep the separators of a reassembled value; its later fragments go.
+        let separators: String = detections
+            .gaps
+            .iter()
+            .filter(|&&(gs, ge)| gs >= start && ge <= end)
+            .map(|&(gs, ge)| &input[gs..ge])
+            .collect();
+        out.push_str(&placeholder);
+        out.push_str(&separators);
+        findings.push(Finding {
+            kind: KINDS[kind],
+            start,
+            end,
+            placeholder,
+            separators,
+            score,
+            rule,
+            action,
+            severity,
+        });
+        last = end;
     }
     out.push_str(&input[last..]);
-    (out, count)
+
+    let counts = RedactionCounts {
+        emails: per_kind[0],
+        ips: per_kind[1],
+        uuids: per_kind[2],
+        jwts: per_kind[3],
+        tokens: per_kind[4],
+        terms: per_kind[5],
+        custom: per_kind[6],
+        allowlisted,
+        allowed,
+        warned,
+        blocked,
+    };
+
+    // TODO(pro-stable-placeholders): gate stable placeholders behind license checks.
+    RedactionResult {
+        text: out,
+        counts,
+        findings,
+    }
 }
 
-fn replace_tokens(input: &str, stable: bool, counter: &mut usize) -> (String, usize) {
-    let mut count = 0usize;
-    let mut out = String::with_capacity(input.len());
-    let mut last = 0usize;
-    for m in TOKEN_CANDIDATE_RE.find_iter(input) {
-        let s = &input[m.start()..m.end()];
-        out.push_str(&input[last..m.start()]);
-        if shannon_entropy(s) >= 3.5 {
-            if stable {
-                *counter += 1;
-                out.push_str("<TOKEN_");
-                out.push_str(&counter.to_string());
-                out.push('>');
-            } else {
-                out.push_str("<TOKEN>");
-            }
-            count += 1;
-        } else {
-            out.push_str(s);
+/// The detected value as the allowlist should see it: the original bytes
+/// with any bridged separators of a reassembled value removed.
+fn span_value(input: &str, start: usize, end: usize, gaps: &[(usize, usize)]) -> String {
+    let mut value = String::with_capacity(end - start);
+    let mut pos = start;
+    for &(gs, ge) in gaps.iter().filter(|&&(gs, ge)| gs >= start && ge <= end) {
+        value.push_str(&input[pos..gs]);
+        pos = ge;
+    }
+    value.push_str(&input[pos..end]);
+    value
+}
+
+/// Merges the spans of `kind` into `spans` (sorted by start, none
+/// overlapping), dropping any that overlap a span already there or an
+/// earlier one of the same kind. Both lists are swept once, in start order.
+fn add_kind(
+    spans: Vec<(usize, usize, usize, usize)>,
+    candidates: &[(usize, usize)],
+    kind: usize,
+) -> Vec<(usize, usize, usize, usize)> {
+    let mut sorted: Vec<(usize, usize, usize)> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| (start, end, i))
+        .collect();
+    sorted.sort();
+    let mut merged = Vec::with_capacity(spans.len() + sorted.len());
+    let mut taken = spans.into_iter().peekable();
+    let mut last_end = 0;
+    for (start, end, i) in sorted {
+        while let Some(span) = taken.next_if(|s| s.0 < start) {
+            last_end = span.1;
+            merged.push(span);
+        }
+        if last_end > start || taken.peek().is_some_and(|s| s.0 < end) {
+            continue;
         }
-        last = m.end();
+        last_end = end;
+        merged.push((start, end, kind, i));
     }
-    out.push_str(&input[last..]);
-    (out, count)
+    merged.extend(taken);
+    merged
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::detectors::detect;
     use crate::ScrubOptions;
 
     #[test]
     fn stable_placeholders_increment() {
         let opts = ScrubOptions {
             stable_placeholders: true,
+            ..ScrubOptions::default()
         };
-        let det = Detections::default();
         let input = "a@b.com a@b.com";
-        let redacted = redact(input, &det, opts);
+        let det = detect(input);
+        let redacted = redact(input, &det, &opts);
         assert!(redacted.text.contains("<EMAIL_1>"));
         assert!(redacted.text.contains("<EMAIL_2>"));
     }
+
+    #[test]
+    fn redacts_original_bytes_of_obfuscated_match() {
+        let input = "to: bob\u{200B}＠example.com!";
+        let det = detect(input);
+        let redacted = redact(input, &det, &ScrubOptions::default());
+        assert_eq!(redacted.text, "to: <EMAIL>!");
+        assert_eq!(redacted.counts.emails, 1);
+    }
+
+    #[test]
+    fn blanks_every_fragment_of_reassembled_token() {
+        let opts = ScrubOptions {
+            reassemble: true,
+            ..ScrubOptions::default()
+        };
+        let input = r#"k = "AbCDeF0123456789" + "AbCDeF0123456789";"#;
+        let det = crate::detectors::detect_with_options(input, &opts);
+        let redacted = redact(input, &det, &opts);
+        assert_eq!(redacted.text, r#"k = "<TOKEN>" + "";"#);
+        assert_eq!(redacted.counts.tokens, 1);
+    }
+
+    #[test]
+    fn allowlisted_values_are_kept_and_counted() {
+        let mut opts = ScrubOptions::default();
+        for (k, v) in [
+            ("allow", "support@acme.io"),
+            ("allow_domain", "example.com"),
+        ] {
+            let entry = crate::allowlist::AllowEntry::from_config(k, v)
+                .unwrap()
+                .unwrap();
+            opts.allowlist.push(entry);
+        }
+        let input = "support@acme.io bob@example.com eve@evil.io";
+        let det = detect(input);
+        let redacted = redact(input, &det, &opts);
+        assert_eq!(redacted.text, "support@acme.io bob@example.com <EMAIL>");
+        assert_eq!(redacted.counts.emails, 1);
+        assert_eq!(redacted.counts.allowlisted, 2);
+    }
+
+    #[test]
+    fn denylisted_terms_use_entry_labels() {
+        let opts = ScrubOptions {
+            stable_placeholders: true,
+            denylist: crate::denylist::Denylist::new(vec![
+                ("Globex".to_string(), Some("CUSTOMER".to_string())),
+                ("Falcon".to_string(), None),
+            ])
+            .unwrap(),
+            ..ScrubOptions::default()
+        };
+        let input = "GLOBEX asked about Falcon; mail ops@globex.com";
+        let det = crate::detectors::detect_with_options(input, &opts);
+        let redacted = redact(input, &det, &opts);
+        assert_eq!(
+            redacted.text,
+            "<CUSTOMER_1> asked about <TERM_1>; mail <EMAIL_1>"
+        );
+        assert_eq!(redacted.counts.terms, 2);
+    }
+
+    #[test]
+    fn custom_rules_win_over_builtin_kinds() {
+        let mut draft = crate::rules::RuleDraft::new("internal-id", 1).unwrap();
+        draft.set("regex", r"ACME-[A-Za-z0-9]{32}", 2).unwrap();
+        let opts = ScrubOptions {
+            rules: vec![draft.build().unwrap()],
+            ..ScrubOptions::default()
+        };
+        let input = "ref ACME-AbCDeF0123456789AbCDeF0123456789 ok";
+        let det = crate::detectors::detect_with_options(input, &opts);
+        let redacted = redact(input, &det, &opts);
+        assert_eq!(redacted.text, "ref <INTERNAL_ID> ok");
+        assert_eq!(redacted.counts.custom, 1);
+        assert_eq!(redacted.counts.tokens, 0);
+    }
+
+    #[test]
+    fn higher_priority_spans_win_overlaps() {
+        let spans = add_kind(Vec::new(), &[(12, 20)], CUSTOM);
+        let spans = add_kind(spans, &[(5, 15), (30, 40)], 0);
+        let spans = add_kind(spans, &[(0, 10), (35, 50), (18, 25), (45, 60)], TOKEN);
+        assert_eq!(
+            spans,
+            vec![
+                (0, 10, TOKEN, 0),
+                (12, 20, CUSTOM, 0),
+                (30, 40, 0, 1),
+                (45, 60, TOKEN, 3)
+            ]
+        );
+    }
+
+    #[test]
+    fn policy_actions_warn_block_and_allow() {
+        let mut opts = ScrubOptions::default();
+        opts.policy.set("email", Action::Warn);
+        opts.policy.set("ip", Action::Allow);
+        opts.policy.set("uuid", Action::Block);
+        let input = "a@b.io 8.8.8.8 550e8400-e29b-41d4-a716-446655440000";
+        let det = crate::detectors::detect_with_options(input, &opts);
+        let redacted = redact(input, &det, &opts);
+        assert_eq!(redacted.text, "a@b.io 8.8.8.8 <UUID>");
+        let c = &redacted.counts;
+        assert_eq!((c.emails, c.ips, c.uuids), (0, 0, 1));
+        assert_eq!((c.warned, c.allowed, c.blocked), (1, 1, 1));
+        assert_eq!(c.allowlisted, 0);
+        let blocked = opts.policy.blocked_by(&redacted.findings);
+        assert_eq!(blocked, vec![("uuid".to_string(), 1)]);
+    }
+
+    #[test]
+    fn findings_carry_kind_and_rule_severity() {
+        let mut draft = crate::rules::RuleDraft::new("ticket", 1).unwrap();
+        draft.set("regex", r"TCK-\d+", 2).unwrap();
+        let mut opts = ScrubOptions {
+            rules: vec![draft.build().unwrap()],
+            ..ScrubOptions::default()
+        };
+        opts.severities.set("custom", Severity::Low);
+        let input = "TCK-42 a@b.io";
+        let det = crate::detectors::detect_with_options(input, &opts);
+        let levels: Vec<Severity> = redact(input, &det, &opts)
+            .findings
+            .iter()
+            .map(|f| f.severity)
+            .collect();
+        assert_eq!(levels, vec![Severity::Low, Severity::Medium]);
+
+        opts.rules[0].severity = Some(Severity::Critical);
+        let findings = redact(input, &det, &opts).findings;
+        assert_eq!(findings[0].severity, Severity::Critical);
+    }
 }
@@ -0,0 +1,346 @@
+//! The versioned JSON report printed with `--json`.
+//!
+//! Every report has the same shape, whether it covers the clipboard, stdin
+//! or many files: metadata about the run, then one entry per input with its
+//! findings, then the totals. Fields are only ever added within a
+//! `schema_version`; renaming or removing one bumps it.
+
+use std::collections::BTreeMap;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::allowlist::hash_value;
+use crate::location::LineIndex;
+use crate::redactor::Finding;
+use crate::severity::Severity;
+use crate::{ScrubOptions, Summary};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub tool: Tool,
+    pub options: OptionsUsed,
+    pub timing: Timing,
+    pub inputs: Vec<InputReport>,
+    /// Files that were not scanned.
+    pub skipped: Vec<SkippedInput>,
+    /// Totals over every input.
+    pub summary: SummaryReport,
+}
+
+impl Report {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("report serializes")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    pub name: &'static str,
+    pub version: &'static str,
+}
+
+/// The scrub options the run used.
+#[derive(Debug, Clone, Serialize)]
+pub struct OptionsUsed {
+    pub stable_placeholders: bool,
+    pub reassemble: bool,
+    pub token_threshold: f64,
+    /// Built-in detectors that ran.
+    pub detectors: Vec<&'static str>,
+    /// Names of the custom rules.
+    pub rules: Vec<String>,
+    pub allowlist_entries: usize,
+    pub denylist_terms: usize,
+    /// Policy entries other than the default `redact`, by key.
+    pub policy: BTreeMap<String, &'static str>,
+}
+
+impl OptionsUsed {
+    pub fn new(options: &ScrubOptions) -> OptionsUsed {
+        OptionsUsed {
+            stable_placeholders: options.stable_placeholders,
+            reassemble: options.reassemble,
+            token_threshold: options.token_threshold,
+            detectors: options.detectors.enabled(),
+            rules: options.rules.iter().map(|r| r.name.clone()).collect(),
+            allowlist_entries: options.allowlist.entries().len(),
+            denylist_terms: options.denylist.len(),
+            policy: options
+                .policy
+                .actions()
+                .map(|(key, action)| (key.to_string(), action.name()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Timing {
+    /// Milliseconds since the Unix epoch when the run started.
+    pub started_at_ms: u64,
+    pub duration_ms: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Clipboard,
+    Stdin,
+    File,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InputReport {
+    /// `clipboard`, `<stdin>` or the file's path.
+    pub name: String,
+    pub source: Source,
+    pub bytes: usize,
+    pub lines: usize,
+    pub summary: SummaryReport,
+    pub findings: Vec<FindingReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedInput {
+    pub name: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SummaryReport {
+    pub emails: usize,
+    pub ips: usize,
+    pub uuids: usize,
+    pub jwts: usize,
+    pub tokens: usize,
+    pub terms: usize,
+    pub custom: usize,
+    pub allowlisted: usize,
+    /// Left in place by an `allow` policy.
+    pub allowed: usize,
+    pub warned: usize,
+    pub blocked: usize,
+    pub severities: SeverityCounts,
+    pub max_severity: Option<Severity>,
+    /// Nothing was left in place by a `warn` policy and nothing was blocked.
+    pub safe_to_paste: bool,
+}
+
+impl From<&Summary> for SummaryReport {
+    fn from(s: &Summary) -> SummaryReport {
+        SummaryReport {
+            emails: s.emails,
+            ips: s.ips,
+            uuids: s.uuids,
+            jwts: s.jwts,
+            tokens: s.tokens,
+            terms: s.terms,
+            custom: s.custom,
+            allowlisted: s.allowlisted,
+            allowed: s.allowed,
+            warned: s.warned,
+            blocked: s.blocked,
+            severities: SeverityCounts {
+                low: s.severities[Severity::Low as usize],
+                medium: s.severities[Severity::Medium as usize],
+                high: s.severities[Severity::High as usize],
+                critical: s.severities[Severity::Critical as usize],
+            },
+            max_severity: s.max_severity(),
+            safe_to_paste: s.warned == 0 && s.blocked == 0,
+        }
+    }
+}
+
+/// Findings (redacted or warned) per severity.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct SeverityCounts {
+    pub low: usize,
+    pub medium: usize,
+    pub high: usize,
+    pub critical: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FindingReport {
+    pub kind: &'static str,
+    pub rule: Option<String>,
+    pub span: Span,
+    pub severity: Severity,
+    /// `redact`, `warn` or `block`.
+    pub action: &'static str,
+    /// `None` when the value was left in place.
+    pub placeholder: Option<String>,
+    /// Confidence score of a heuristically detected token.
+    pub score: Option<f64>,
+    /// Hex SHA-256 of the value, as used by `allow_sha256`.
+    pub value_sha256: String,
+}
+
+/// Where a finding is: byte offsets and 1-based line and character column
+/// of its start and end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl FindingReport {
+    pub fn new(text: &str, index: &LineIndex, f: &Finding) -> FindingReport {
+        let (line, column) = index.locate(f.start);
+        let (end_line, end_column) = index.locate(f.end);
+        FindingReport {
+            kind: f.kind,
+            rule: f.rule.clone(),
+            span: Span {
+                start: f.start,
+                end: f.end,
+                line,
+                column,
+                end_line,
+                end_column,
+            },
+            severity: f.severity,
+            action: f.action.name(),
+            placeholder: Some(f.placeholder.clone()).filter(|p| !p.is_empty()),
+            score: f.score,
+            value_sha256: hash_value(&text[f.start..f.end]),
+        }
+    }
+}
+
+/// Collects inputs into a [`Report`], timing the run from its creation.
+pub struct ReportBuilder {
+    options: OptionsUsed,
+    started: SystemTime,
+    clock: Instant,
+    inputs: Vec<InputReport>,
+    skipped: Vec<SkippedInput>,
+    total: Summary,
+}
+
+impl ReportBuilder {
+    pub fn new(options: &ScrubOptions) -> ReportBuilder {
+        ReportBuilder {
+            options: OptionsUsed::new(options),
+            started: SystemTime::now(),
+            clock: Instant::now(),
+            inputs: Vec::new(),
+            skipped: Vec::new(),
+            total: Summary::default(),
+        }
+    }
+
+    /// Adds `text`, scrubbed into `summary` and `findings`.
+    pub fn input(
+        &mut self,
+        name: &str,
+        source: Source,
+        text: &str,
+        summary: &Summary,
+        findings: &[Finding],
+    ) {
+        let index = LineIndex::new(text);
+        self.total.add(summary);
+        self.inputs.push(InputReport {
+            name: name.to_string(),
+            source,
+            bytes: text.len(),
+            lines: if text.is_empty() {
+                0
+            } else {
+                text.lines().count()
+            },
+            summary: summary.into(),
+            findings: findings
+                .iter()
+                .map(|f| FindingReport::new(text, &index, f))
+                .collect(),
+        });
+    }
+
+    pub fn skipped(&mut self, name: &str, reason: &str) {
+        self.skipped.push(SkippedInput {
+            name: name.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    pub fn finish(self) -> Report {
+        let started_at_ms = self
+            .started
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        Report {
+            schema_version: SCHEMA_VERSION,
+            tool: Tool {
+                name: env!("CARGO_PKG_NAME"),
+                version: env!("CARGO_PKG_VERSION"),
+            },
+            options: self.options,
+            timing: Timing {
+                started_at_ms,
+                duration_ms: self.clock.elapsed().as_secs_f64() * 1000.0,
+            },
+            inputs: self.inputs,
+            skipped: self.skipped,
+            summary: (&self.total).into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Action;
+    use crate::scrub_text_with_findings;
+
+    #[test]
+    fn reports_findings_with_locations_and_hashes() {
+        let mut options = ScrubOptions::default();
+        options.policy.set("ip", Action::Warn);
+        let text = "hi\nmail a@b.io from 10.0.0.1\n";
+        let (_, summary, findings) = scrub_text_with_findings(text, &options);
+        let mut builder = ReportBuilder::new(&options);
+        builder.input("notes.txt", Source::File, text, &summary, &findings);
+        builder.skipped("logo.png", "binary file");
+        let json: serde_json::Value = serde_json::from_str(&builder.finish().to_json()).unwrap();
+
+        assert_eq!(json["schema_version"], 1);
+        assert_eq!(json["tool"]["name"], "scrubby");
+        assert_eq!(json["options"]["policy"]["ip"], "warn");
+        let input = &json["inputs"][0];
+        assert_eq!(input["source"], "file");
+        assert_eq!(
+            (input["bytes"].as_u64(), input["lines"].as_u64()),
+            (Some(29), Some(2))
+        );
+        assert_eq!(
+            input["findings"][0],
+            serde_json::json!({
+                "kind": "email",
+                "rule": null,
+                "span": {"start": 8, "end": 14, "line": 2, "column": 6, "end_line": 2, "end_column": 12},
+                "severity": "medium",
+                "action": "redact",
+                "placeholder": "<EMAIL>",
+                "score": null,
+                "value_sha256": hash_value("a@b.io"),
+            })
+        );
+        assert_eq!(input["findings"][1]["placeholder"], serde_json::Value::Null);
+        assert_eq!(json["skipped"][0]["reason"], "binary file");
+        assert_eq!(json["summary"]["emails"], 1);
+        assert_eq!(json["summary"]["severities"]["medium"], 1);
+        assert_eq!(json["summary"]["max_severity"], "medium");
+        assert_eq!(json["summary"]["safe_to_paste"], false);
+    }
+}
@@ -0,0 +1,236 @@
+//! Interactive review of findings before they are redacted, for
+//! `scrubby scrub --review`.
+
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+
+use crate::location::LineIndex;
+use crate::policy::Action;
+use crate::redactor::Finding;
+use crate::{scrub_text_with_findings, ScrubOptions};
+
+/// An answer to "redact this finding?".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Choice {
+    Redact,
+    /// Leave this occurrence in place.
+    Keep,
+    /// Leave every occurrence of this value in place for the rest of the run.
+    KeepValue,
+    /// Like `KeepValue`, and remember the value for the allowlist.
+    Allow,
+    /// Redact this and every later finding without asking.
+    RedactRest,
+}
+
+impl Choice {
+    fn from_answer(answer: &str) -> Option<Choice> {
+        match answer.trim().to_ascii_lowercase().as_str() {
+            "" | "y" | "yes" => Some(Choice::Redact),
+            "n" | "no" => Some(Choice::Keep),
+            "v" => Some(Choice::KeepValue),
+            "a" => Some(Choice::Allow),
+            "r" => Some(Choice::RedactRest),
+            _ => None,
+        }
+    }
+}
+
+const PROMPT: &str =
+    "Redact? [Y]es, [n]o, keep this [v]alue everywhere, [a]llowlist it, [r]edact the rest: ";
+
+/// Asks about findings one at a time, reading answers from `input` and
+/// writing the findings and prompts to `output`. Choices about a value carry
+/// over to later inputs of the same run.
+pub struct Reviewer<R, W> {
+    input: R,
+    output: W,
+    kept_values: HashSet<String>,
+    allowed: Vec<String>,
+    redact_rest: bool,
+}
+
+impl<R: BufRead, W: Write> Reviewer<R, W> {
+    pub fn new(input: R, output: W) -> Reviewer<R, W> {
+        Reviewer {
+            input,
+            output,
+            kept_values: HashSet::new(),
+            allowed: Vec::new(),
+            redact_rest: false,
+        }
+    }
+
+    /// Asks about each finding that `options` would redact in `text` (shown
+    /// as `name`) and returns options that leave the kept ones in place.
+    /// Findings a `block` policy matched are not offered, and running out of
+    /// input redacts everything not yet answered.
+    pub fn review(
+        &mut self,
+        name: &str,
+        text: &str,
+        options: &ScrubOptions,
+    ) -> io::Result<ScrubOptions> {
+        let (_, _, findings) = scrub_text_with_findings(text, options);
+        let index = LineIndex::new(text);
+        let mut reviewed = options.clone();
+        let pending: Vec<&Finding> = findings
+            .iter()
+            .filter(|f| f.action == Action::Redact)
+            .collect();
+        for (n, f) in pending.iter().enumerate() {
+            let value = &text[f.start..f.end];
+            if self.kept_values.contains(value) {
+                reviewed.kept.push((f.start, f.end));
+                continue;
+            }
+            if self.redact_rest {
+                continue;
+            }
+            let (line, column) = index.locate(f.start);
+            let kind = match &f.rule {
+                Some(rule) => format!("{} ({})", f.kind, rule),
+                None => f.kind.to_string(),
+            };
+            writeln!(
+                self.output,
+                "\n[{}/{}] {} {} at {}:{}:{}",
+                n + 1,
+                pending.len(),
+                f.severity,
+                kind,
+                name,
+                line,
+                column
+            )?;
+            self.show_context(&index, f)?;
+            match self.ask()? {
+                Choice::Redact => {}
+                Choice::Keep => reviewed.kept.push((f.start, f.end)),
+                Choice::KeepValue => {
+                    self.kept_values.insert(value.to_string());
+                    reviewed.kept.push((f.start, f.end));
+                }
+                Choice::Allow => {
+                    self.kept_values.insert(value.to_string());
+                    self.allowed.push(value.to_string());
+                    reviewed.kept.push((f.start, f.end));
+                }
+                Choice::RedactRest => self.redact_rest = true,
+            }
+        }
+        Ok(reviewed)
+    }
+
+    /// Values allowlisted with `a` since the last call.
+    pub fn take_allowed(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.allowed)
+    }
+
+    /// The finding's line underlined with `^`, between its neighbours unless
+    /// they are blank.
+    fn show_context(&mut self, index: &LineIndex, f: &Finding) -> io::Result<()> {
+        let (first, column) = index.locate(f.start);
+        let (last, end_column) = index.locate(f.end);
+        let width = (last + 1).to_string().len();
+        if first > 1 && !index.line(first - 1).is_empty() {
+            writeln!(
+                self.output,
+                "{:>w$} | {}",
+                first - 1,
+                index.line(first - 1),
+                w = width
+            )?;
+        }
+        let text = index.line(first);
+        writeln!(self.output, "{:>w$} | {}", first, text, w = width)?;
+        let underline = match last == first {
+            true => end_column - column,
+            false => text.chars().count() + 1 - column,
+        };
+        writeln!(
+            self.output,
+            "{:>w$} | {}{}",
+            "",
+            " ".repeat(column - 1),
+            "^".repeat(underline.max(1)),
+            w = width
+        )?;
+        if last > first {
+            writeln!(
+                self.output,
+                "{:>w$} | (continues to line {})",
+                "",
+                last,
+                w = width
+            )?;
+        }
+        if last < index.line_count() && !index.line(last + 1).is_empty() {
+            writeln!(
+                self.output,
+                "{:>w$} | {}",
+                last + 1,
+                index.line(last + 1),
+                w = width
+            )?;
+        }
+        Ok(())
+    }
+
+    fn ask(&mut self) -> io::Result<Choice> {
+        loop {
+            write!(self.output, "{}", PROMPT)?;
+            self.output.flush()?;
+            let mut answer = String::new();
+            if self.input.read_line(&mut answer)? == 0 {
+                writeln!(self.output)?;
+                return Ok(Choice::RedactRest);
+            }
+            match Choice::from_answer(&answer) {
+                Some(choice) => return Ok(choice),
+                None => writeln!(self.output, "Please answer y, n, v, a or r.")?,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrub_after(reviewer: &mut Reviewer<&[u8], Vec<u8>>, text: &str) -> String {
+        let options = reviewer
+            .review("notes", text, &ScrubOptions::default())
+            .unwrap();
+        scrub_text_with_findings(text, &options).0
+    }
+
+    #[test]
+    fn keeps_what_the_user_skips() {
+        let mut reviewer = Reviewer::new(&b"v\nmaybe\ny\nn\n"[..], Vec::new());
+        let text = "a@b.io x@y.io a@b.io c@d.io";
+        assert_eq!(
+            scrub_after(&mut reviewer, text),
+            "a@b.io <EMAIL> a@b.io c@d.io"
+        );
+        // The kept value carries over to the next input without asking.
+        assert_eq!(scrub_after(&mut reviewer, "to a@b.io"), "to a@b.io");
+        let shown = String::from_utf8(reviewer.output).unwrap();
+        assert!(shown.contains("[1/4] medium email at notes:1:1\n1 | a@b.io"));
+        assert!(shown.contains("  | ^^^^^^\n"));
+        assert!(shown.contains("Please answer y, n, v, a or r."));
+        assert_eq!(shown.matches(PROMPT).count(), 4);
+    }
+
+    #[test]
+    fn remembers_allowlisted_values_and_redacts_the_rest_at_end_of_input() {
+        let mut reviewer = Reviewer::new(&b"a\n"[..], Vec::new());
+        let text = "ops@acme.io\nx@y.io\nops@acme.io";
+        assert_eq!(
+            scrub_after(&mut reviewer, text),
+            "ops@acme.io\n<EMAIL>\nops@acme.io"
+        );
+        assert_eq!(reviewer.take_allowed(), vec!["ops@acme.io".to_string()]);
+        assert!(reviewer.take_allowed().is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! Writing sanitized files back to disk.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Replaces `path` with `contents` atomically: the new text is written to a
+/// temporary file in the same directory, which is then renamed over `path`.
+/// An existing file's permissions are kept.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+    let mut tmp = tempfile::Builder::new()
+        .prefix(".scrubby-")
+        .suffix(".tmp")
+        .tempfile_in(dir)?;
+    tmp.write_all(contents.as_bytes())?;
+    tmp.as_file().sync_all()?;
+    if let Ok(meta) = fs::metadata(path) {
+        tmp.as_file().set_permissions(meta.permissions())?;
+    }
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Suffix of the backups `--backup` keeps unless given another.
+pub const DEFAULT_BACKUP_SUFFIX: &str = ".bak";
+
+/// Copies `path` to `path` + `suffix` (e.g. `notes.txt.bak`), replacing an
+/// older backup.
+pub fn backup(path: &Path, suffix: &str) -> io::Result<PathBuf> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    let backup = PathBuf::from(name);
+    fs::copy(path, &backup)?;
+    Ok(backup)
+}
+
+/// Where `file`, found under the argument `root`, goes in the mirror
+/// directory `out_dir`: its path below `root`, or its file name when `root`
+/// is the file itself.
+pub fn mirror_path(out_dir: &Path, root: &Path, file: &Path) -> PathBuf {
+    match file.strip_prefix(root) {
+        Ok(rel) if !rel.as_os_str().is_empty() => out_dir.join(rel),
+        _ => out_dir.join(file.file_name().unwrap_or(file.as_os_str())),
+    }
+}
+
+/// Whether `path` looks like a backup made with `suffix`, which an in-place
+/// run must not scrub in turn.
+pub fn is_backup(path: &Path, suffix: &str) -> bool {
+    path.file_name()
+        .map(|n| n.to_string_lossy())
+        .is_some_and(|n| n.len() > suffix.len() && n.ends_with(suffix))
+}
+
+/// Two of the `(root, file)` pairs that [`mirror_path`] would write to the
+/// same place in `out_dir`, with that place.
+pub fn mirror_collision<'a>(
+    out_dir: &Path,
+    files: impl IntoIterator<Item = (&'a Path, &'a Path)>,
+) -> Option<(PathBuf, PathBuf, PathBuf)> {
+    let mut seen: HashMap<PathBuf, &Path> = HashMap::new();
+    for (root, file) in files {
+        let target = mirror_path(out_dir, root, file);
+        match seen.get(&target) {
+            Some(&first) if first != file => {
+                return Some((first.to_path_buf(), file.to_path_buf(), target))
+            }
+            Some(_) => {}
+            None => {
+                seen.insert(target, file);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_files_keeping_permissions_and_backups() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        fs::write(&path, "mail a@b.io\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o640)).unwrap();
+        }
+
+        let bak = backup(&path, ".bak").unwrap();
+        write_atomic(&path, "mail <EMAIL>\n").unwrap();
+        assert_eq!(fs::read_to_string(&bak).unwrap(), "mail a@b.io\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "mail <EMAIL>\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+        let leftovers = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(leftovers, 2);
+    }
+
+    #[test]
+    fn mirrors_paths_below_their_root() {
+        let out = Path::new("clean");
+        assert_eq!(
+            mirror_path(out, Path::new("logs"), Path::new("logs/a/b.txt")),
+            Path::new("clean/a/b.txt")
+        );
+        assert_eq!(
+            mirror_path(out, Path::new("logs/b.txt"), Path::new("logs/b.txt")),
+            Path::new("clean/b.txt")
+        );
+
+        let (a, b) = (Path::new("a/app.log"), Path::new("b/app.log"));
+        assert_eq!(
+            mirror_collision(out, [(a, a), (b, b)]),
+            Some((a.to_path_buf(), b.to_path_buf(), out.join("app.log")))
+        );
+        assert_eq!(mirror_collision(out, [(a, a), (a, a)]), None);
+        let (logs, c) = (Path::new("logs"), Path::new("logs/a/app.log"));
+        assert_eq!(mirror_collision(out, [(a, a), (logs, c)]), None);
+    }
+
+    #[test]
+    fn recognises_backups() {
+        assert!(is_backup(Path::new("logs/app.log.bak"), ".bak"));
+        assert!(!is_backup(Path::new("logs/app.log"), ".bak"));
+        assert!(!is_backup(Path::new("logs/.bak"), ".bak"));
+    }
+}
@@ -0,0 +1,484 @@
+//! SARIF 2.1.0 output of `scrubby check --format sarif`, for code-scanning
+//! tools.
+//!
+//! Each detector and custom rule is a SARIF rule; each finding is a result
+//! located by line and column. Results carry the SHA-256 of the value as a
+//! partial fingerprint, so a consumer can match a finding across runs after
+//! the lines around it moved, without the value itself leaving the machine.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::allowlist::hash_value;
+use crate::location::LineIndex;
+use crate::redactor::{pattern_id, Finding};
+use crate::severity::Severity;
+use crate::ScrubOptions;
+
+pub const SARIF_VERSION: &str = "2.1.0";
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SarifLog {
+    #[serde(rename = "$schema")]
+    pub schema: &'static str,
+    pub version: &'static str,
+    pub runs: Vec<Run>,
+}
+
+impl SarifLog {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("SARIF log serializes")
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Run {
+    pub tool: Tool,
+    pub invocations: Vec<Invocation>,
+    /// Columns count characters, as in the text output.
+    pub column_kind: &'static str,
+    pub results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Tool {
+    pub driver: Driver,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Driver {
+    pub name: &'static str,
+    pub semantic_version: &'static str,
+    pub rules: Vec<Rule>,
+}
+
+/// A SARIF `reportingDescriptor`: one detector or custom rule.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    /// The pattern id, e.g. `builtin.email` or `rule.aws_key`.
+    pub id: String,
+    pub name: String,
+    pub short_description: Message,
+    pub full_description: Message,
+    pub default_configuration: Configuration,
+    pub properties: RuleProperties,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Message {
+    pub text: String,
+}
+
+impl Message {
+    fn new(text: impl Into<String>) -> Message {
+        Message { text: text.into() }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Configuration {
+    pub level: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleProperties {
+    pub tags: Vec<String>,
+    /// The 0.0–10.0 score code-scanning UIs rank security alerts by.
+    #[serde(rename = "security-severity")]
+    pub security_severity: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Invocation {
+    /// False when an input could not be read.
+    pub execution_successful: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tool_execution_notifications: Vec<Notification>,
+}
+
+/// A skipped input (`note`) or one that could not be read (`error`).
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub level: &'static str,
+    pub message: Message,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SarifResult {
+    pub rule_id: String,
+    pub rule_index: usize,
+    pub level: &'static str,
+    pub message: Message,
+    pub locations: Vec<Location>,
+    /// `scrubby/v1`: the rule, file and value together.
+    pub fingerprints: BTreeMap<&'static str, String>,
+    /// `valueSha256/v1`: the value alone, as used by `allow_sha256`.
+    pub partial_fingerprints: BTreeMap<&'static str, String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Location {
+    pub physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhysicalLocation {
+    pub artifact_location: ArtifactLocation,
+    pub region: Region,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtifactLocation {
+    pub uri: String,
+}
+
+/// 1-based; `end_column` is just past the value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Region {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+/// SARIF level of findings of `severity`.
+pub fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "note",
+        Severity::Medium => "warning",
+        Severity::High | Severity::Critical => "error",
+    }
+}
+
+fn security_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Low => "3.0",
+        Severity::Medium => "5.0",
+        Severity::High => "8.0",
+        Severity::Critical => "9.5",
+    }
+}
+
+/// Name, short and full description of a built-in kind.
+fn describe(kind: &str) -> (&'static str, &'static str, &'static str) {
+    match kind {
+        "email" => (
+            "EmailAddress",
+            "Email address",
+            "An email address, which identifies a person or account.",
+        ),
+        "ip" => (
+            "IpAddress",
+            "IP address",
+            "An IPv4 address, which can reveal internal hosts or network layout.",
+        ),
+        "uuid" => (
+            "Uuid",
+            "UUID",
+            "A UUID, often the id of an account, customer or resource.",
+        ),
+        "jwt" => (
+            "JsonWebToken",
+            "JSON Web Token",
+            "A JSON Web Token, usually a live bearer credential.",
+        ),
+        "token" => (
+            "SecretToken",
+            "Secret token",
+            "A high-entropy string that looks like an API key, password or other credential.",
+        ),
+        "term" => (
+            "DenylistedTerm",
+            "Denylisted term",
+            "A term from a denylist that must not leave the machine.",
+        ),
+        _ => (
+            "Custom",
+            "Custom finding",
+            "A value matched by a custom rule.",
+        ),
+    }
+}
+
+/// `name` as a relative or `file://` URI, percent-encoding what a URI path
+/// cannot hold.
+pub fn artifact_uri(name: &str) -> String {
+    let path = name.replace('\\', "/");
+    let path = path.strip_prefix("./").unwrap_or(&path);
+    let mut uri = String::new();
+    if path.starts_with('/') {
+        uri.push_str("file://");
+    } else if path.as_bytes().get(1) == Some(&b':') {
+        // A Windows drive letter.
+        uri.push_str("file:///");
+    }
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => uri.push(b as char),
+            b'-' | b'.' | b'_' | b'~' | b'/' | b':' | b'@' | b'+' | b'=' => uri.push(b as char),
+            _ => uri.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    uri
+}
+
+/// Collects findings into a [`SarifLog`] with a single run.
+pub struct SarifBuilder {
+    rules: Vec<Rule>,
+    results: Vec<SarifResult>,
+    notifications: Vec<Notification>,
+    successful: bool,
+}
+
+impl SarifBuilder {
+    /// Starts a log with a rule for every detector `options` run, the
+    /// denylist if it has terms, and every custom rule.
+    pub fn new(options: &ScrubOptions) -> SarifBuilder {
+        let mut builder = SarifBuilder {
+            rules: Vec::new(),
+            results: Vec::new(),
+            notifications: Vec::new(),
+            successful: true,
+        };
+        for kind in options.detectors.enabled() {
+            builder.rule_index(options, kind, None);
+        }
+        if !options.denylist.is_empty() {
+            builder.rule_index(options, "term", None);
+        }
+        for rule in options.rules.iter() {
+            builder.rule_index(options, "custom", Some(&rule.name));
+        }
+        builder
+    }
+
+    /// Adds the findings in `text`, named `name`. `line_numbers`, when the
+    /// text is not a whole file, gives the file's line number of each line.
+    pub fn input(
+        &mut self,
+        options: &ScrubOptions,
+        name: &str,
+        text: &str,
+        line_numbers: Option<&[usize]>,
+        findings: &[Finding],
+    ) {
+        let index = LineIndex::new(text);
+        let uri = artifact_uri(name);
+        let file_line = |line: usize| line_numbers.map_or(line, |numbers| numbers[line - 1]);
+        for f in findings {
+            let rule_index = self.rule_index(options, f.kind, f.rule.as_deref());
+            let rule_id = self.rules[rule_index].id.clone();
+            let (start_line, start_column) = index.locate(f.start);
+            let (end_line, end_column) = index.locate(f.end);
+            let value_hash = hash_value(&text[f.start..f.end]);
+            let fingerprint = hash_value(&format!("{}\0{}\0{}", rule_id, uri, value_hash));
+            let short = &self.rules[rule_index].short_description.text;
+            self.results.push(SarifResult {
+                message: Message::new(format!("{} ({} severity)", short, f.severity)),
+                rule_id,
+                rule_index,
+                level: level(f.severity),
+                locations: vec![Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation { uri: uri.clone() },
+                        region: Region {
+                            start_line: file_line(start_line),
+                            start_column,
+                            end_line: file_line(end_line),
+                            end_column,
+                        },
+                    },
+                }],
+                fingerprints: BTreeMap::from([("scrubby/v1", fingerprint)]),
+                partial_fingerprints: BTreeMap::from([("valueSha256/v1", value_hash)]),
+            });
+        }
+    }
+
+    pub fn skipped(&mut self, name: &str, reason: &str) {
+        self.notifications.push(Notification {
+            level: "note",
+            message: Message::new(format!("Skipped {}: {}", name, reason)),
+        });
+    }
+
+    /// Records an input that could not be read.
+    pub fn failed(&mut self, message: &str) {
+        self.successful = false;
+        self.notifications.push(Notification {
+            level: "error",
+            message: Message::new(message),
+        });
+    }
+
+    pub fn finish(self) -> SarifLog {
+        SarifLog {
+            schema: SARIF_SCHEMA,
+            version: SARIF_VERSION,
+            runs: vec![Run {
+                tool: Tool {
+                    driver: Driver {
+                        name: env!("CARGO_PKG_NAME"),
+                        semantic_version: env!("CARGO_PKG_VERSION"),
+                        rules: self.rules,
+                    },
+                },
+                invocations: vec![Invocation {
+                    execution_successful: self.successful,
+                    tool_execution_notifications: self.notifications,
+                }],
+                column_kind: "unicodeCodePoints",
+                results: self.results,
+            }],
+        }
+    }
+
+    /// Index of the rule for findings of `kind` from `rule`, adding it on
+    /// first use.
+    fn rule_index(&mut self, options: &ScrubOptions, kind: &str, rule: Option<&str>) -> usize {
+        let id = pattern_id(kind, rule);
+        if let Some(i) = self.rules.iter().position(|r| r.id == id) {
+            return i;
+        }
+        let custom = rule.and_then(|name| options.rules.iter().find(|r| r.name == name));
+        let severity = custom
+            .and_then(|r| r.severity)
+            .unwrap_or_else(|| options.severities.get(kind));
+        let (name, short, full) = match (rule, custom) {
+            (Some(name), Some(r)) => (
+                name.to_string(),
+                format!("Custom rule {}", name),
+                format!(
+                    "A value matched by the custom rule {} (`{}`).",
+                    name, r.regex
+                ),
+            ),
+            (Some(name), None) => (
+                name.to_string(),
+                format!("Custom rule {}", name),
+                format!("A value matched by the custom rule {}.", name),
+            ),
+            (None, _) => {
+                let (name, short, full) = describe(kind);
+                (name.to_string(), short.to_string(), full.to_string())
+            }
+        };
+        self.rules.push(Rule {
+            id,
+            name,
+            short_description: Message::new(short),
+            full_description: Message::new(full),
+            default_configuration: Configuration {
+                level: level(severity),
+            },
+            properties: RuleProperties {
+                tags: vec!["security".to_string(), kind.to_string()],
+                security_severity: security_severity(severity),
+            },
+        });
+        self.rules.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleDraft;
+    use crate::scrub_text_with_findings;
+
+    #[test]
+    fn reports_rules_results_and_fingerprints() {
+        let mut draft = RuleDraft::new("ticket", 1).unwrap();
+        draft.set("regex", r"TCK-\d+", 1).unwrap();
+        let options = ScrubOptions {
+            rules: vec![draft.build().unwrap()],
+            ..ScrubOptions::default()
+        };
+        let text = "hi\nmail a@b.io about TCK-42\n";
+        let (_, _, findings) = scrub_text_with_findings(text, &options);
+        let mut builder = SarifBuilder::new(&options);
+        builder.input(&options, "logs/app log.txt", text, None, &findings);
+        // The same lines, as a staged diff starting at line 40.
+        builder.input(
+            &options,
+            "logs/app log.txt",
+            text,
+            Some(&[40, 41]),
+            &findings,
+        );
+        builder.skipped("logo.png", "binary file");
+        let log = builder.finish();
+        let json: serde_json::Value = serde_json::from_str(&log.to_json()).unwrap();
+
+        assert_eq!(json["version"], "2.1.0");
+        let run = &json["runs"][0];
+        let ids: Vec<&str> = run["tool"]["driver"]["rules"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "builtin.email",
+                "builtin.ip",
+                "builtin.uuid",
+                "builtin.jwt",
+                "builtin.token",
+                "rule.ticket"
+            ]
+        );
+        assert_eq!(
+            run["tool"]["driver"]["rules"][4]["properties"]["security-severity"],
+            "8.0"
+        );
+
+        let email = &run["results"][0];
+        assert_eq!(email["ruleId"], "builtin.email");
+        assert_eq!(email["ruleIndex"], 0);
+        assert_eq!(email["level"], "warning");
+        assert_eq!(email["message"]["text"], "Email address (medium severity)");
+        let location = &email["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "logs/app%20log.txt");
+        assert_eq!(
+            location["region"],
+            serde_json::json!({"startLine": 2, "startColumn": 6, "endLine": 2, "endColumn": 12})
+        );
+        assert_eq!(
+            email["partialFingerprints"]["valueSha256/v1"],
+            hash_value("a@b.io")
+        );
+        assert_eq!(run["results"][1]["ruleIndex"], 5);
+
+        // Moving the finding keeps its fingerprints.
+        let moved = &run["results"][2];
+        assert_eq!(
+            moved["locations"][0]["physicalLocation"]["region"]["startLine"],
+            41
+        );
+        assert_eq!(moved["fingerprints"], email["fingerprints"]);
+        assert_eq!(
+            run["invocations"][0]["toolExecutionNotifications"][0]["message"]["text"],
+            "Skipped logo.png: binary file"
+        );
+    }
+
+    #[test]
+    fn turns_paths_into_uris() {
+        assert_eq!(artifact_uri("./src/main.rs"), "src/main.rs");
+        assert_eq!(artifact_uri("/tmp/a b.txt"), "file:///tmp/a%20b.txt");
+        assert_eq!(artifact_uri(r"C:\logs\app.log"), "file:///C:/logs/app.log");
+        assert_eq!(artifact_uri("<stdin>"), "%3Cstdin%3E");
+    }
+}
@@ -0,0 +1,345 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::detectors::shannon_entropy;
+
+/// Tokens scoring at or above this are redacted unless configured otherwise.
+pub const DEFAULT_TOKEN_THRESHOLD: f64 = 0.6;
+
+/// The narrowest character set a candidate is drawn from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    Hex,
+    Base32,
+    Alphanumeric,
+    Base64Url,
+    Base64,
+    Printable,
+}
+
+impl Alphabet {
+    pub const ALL: [Alphabet; 6] = [
+        Alphabet::Hex,
+        Alphabet::Base32,
+        Alphabet::Alphanumeric,
+        Alphabet::Base64Url,
+        Alphabet::Base64,
+        Alphabet::Printable,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Alphabet> {
+        Alphabet::ALL.into_iter().find(|a| a.name() == name)
+    }
+
+    pub fn of(s: &str) -> Alphabet {
+        let all = |f: fn(char) -> bool| s.chars().all(f);
+        if all(|c| c.is_ascii_hexdigit()) {
+            Alphabet::Hex
+        } else if all(|c| c.is_ascii_uppercase() || ('2'..='7').contains(&c) || c == '=') {
+            Alphabet::Base32
+        } else if all(|c| c.is_ascii_alphanumeric()) {
+            Alphabet::Alphanumeric
+        } else if all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            Alphabet::Base64Url
+        } else if all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '/' || c == '=') {
+            Alphabet::Base64
+        } else {
+            Alphabet::Printable
+        }
+    }
+
+    /// Maximum entropy in bits per character for this alphabet.
+    pub fn bits(self) -> f64 {
+        match self {
+            Alphabet::Hex => 4.0,
+            Alphabet::Base32 => 5.0,
+            Alphabet::Alphanumeric => 62f64.log2(),
+            Alphabet::Base64Url | Alphabet::Base64 => 6.0,
+            Alphabet::Printable => 95f64.log2(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Alphabet::Hex => "hex",
+            Alphabet::Base32 => "base32",
+            Alphabet::Alphanumeric => "alphanumeric",
+            Alphabet::Base64Url => "base64url",
+            Alphabet::Base64 => "base64",
+            Alphabet::Printable => "printable",
+        }
+    }
+}
+
+/// Minimum Shannon entropy (bits per character) per alphabet. Values shorter
+/// than the alphabet size cannot reach full entropy, so they are held to a
+/// proportionally lower floor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyThresholds {
+    pub hex: f64,
+    pub base32: f64,
+    pub alphanumeric: f64,
+    pub base64url: f64,
+    pub base64: f64,
+    pub printable: f64,
+}
+
+impl Default for EntropyThresholds {
+    fn default() -> Self {
+        EntropyThresholds {
+            hex: 3.0,
+            base32: 3.5,
+            alphanumeric: 3.5,
+            base64url: 3.75,
+            base64: 3.75,
+            printable: 3.75,
+        }
+    }
+}
+
+impl EntropyThresholds {
+    pub fn get(&self, alphabet: Alphabet) -> f64 {
+        match alphabet {
+            Alphabet::Hex => self.hex,
+            Alphabet::Base32 => self.base32,
+            Alphabet::Alphanumeric => self.alphanumeric,
+            Alphabet::Base64Url => self.base64url,
+            Alphabet::Base64 => self.base64,
+            Alphabet::Printable => self.printable,
+        }
+    }
+
+    pub fn set(&mut self, alphabet: Alphabet, bits: f64) {
+        let slot = match alphabet {
+            Alphabet::Hex => &mut self.hex,
+            Alphabet::Base32 => &mut self.base32,
+            Alphabet::Alphanumeric => &mut self.alphanumeric,
+            Alphabet::Base64Url => &mut self.base64url,
+            Alphabet::Base64 => &mut self.base64,
+            Alphabet::Printable => &mut self.printable,
+        };
+        *slot = bits;
+    }
+
+    /// Whether `candidate` clears the floor for its own alphabet.
+    pub fn passes(&self, candidate: &str) -> bool {
+        let alphabet = Alphabet::of(candidate);
+        let len = candidate.chars().count() as f64;
+        let scale = (len.log2() / alphabet.bits()).min(1.0);
+        shannon_entropy(candidate) >= self.get(alphabet) * scale
+    }
+}
+
+/// A whole identifier segment naming a secret, alone or glued to a
+/// qualifier as in `accesstoken`.
+static KEYWORD_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(?:api|access|private|client|session|refresh|app|master|signing|secret|auth)?(?:secrets?|tokens?|passw(?:or)?ds?|pwd|auth|authorization|bearer|credentials?)$|^(?:api|access|private|client|app|master|signing|secret|auth|ssh|encryption|license)keys?$",
+    )
+    .unwrap()
+});
+
+/// Segments that make a following `key` segment a secret (`api_key`, but
+/// not `sort_key` or `primary_key`).
+const KEY_QUALIFIERS: [&str; 12] = [
+    "api",
+    "access",
+    "private",
+    "client",
+    "app",
+    "master",
+    "signing",
+    "secret",
+    "auth",
+    "ssh",
+    "encryption",
+    "license",
+];
+
+static BENIGN_REGION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\bsha(?:1|256|384|512)-[A-Za-z0-9+/]+=*|data:[\w/.+-]+;base64,[A-Za-z0-9+/]+=*")
+        .unwrap()
+});
+
+static DIGEST_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(?:[0-9a-f]{40}|[0-9a-f]{64}|[0-9a-f]{128}|[0-9A-F]{40}|[0-9A-F]{64})$").unwrap()
+});
+
+/// Returns a 0.0..=1.0 confidence that `candidate` is a secret. `context` is
+/// the text just before it on the same line; `in_benign_region` marks values
+/// inside npm integrity hashes or base64 data URIs.
+pub fn score_token(candidate: &str, context: &str, in_benign_region: bool) -> f64 {
+    let len = candidate.chars().count();
+    if len == 0 {
+        return 0.0;
+    }
+
+    let max_entropy = (len as f64)
+        .log2()
+        .clamp(1.0, Alphabet::of(candidate).bits());
+    let entropy = (shannon_entropy(candidate) / max_entropy).min(1.0);
+    let length = (len as f64 / 32.0).min(1.0);
+    let mut score = 0.35 * entropy + 0.15 * char_class_mix(candidate) + 0.3 * length;
+
+    let keyword = has_keyword(context);
+    if keyword {
+        score += 0.3;
+    } else if in_benign_region || DIGEST_RE.is_match(candidate) {
+        score *= 0.4;
+    } else if !candidate.chars().any(|c| c.is_ascii_digit()) {
+        // Long digit-free runs are almost always identifiers or words.
+        score *= 0.6;
+    }
+
+    score.min(1.0)
+}
+
+/// Whether a word in `context` names a secret, e.g. `password`, `API_KEY` or
+/// `accessToken`. Keywords must be whole identifier segments, so `monkey`,
+/// `author` and `keyboard` do not count; a bare `key` segment only counts on
+/// its own or after a qualifier such as `api`.
+pub fn has_keyword(context: &str) -> bool {
+    context
+        .split(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.')))
+        .any(is_secret_name)
+}
+
+/// Whether the identifier `name` names a secret, by the rules of
+/// [`has_keyword`].
+pub fn is_secret_name(name: &str) -> bool {
+    let segments = identifier_segments(name);
+    segments.iter().enumerate().any(|(i, seg)| {
+        KEYWORD_RE.is_match(seg)
+            || (matches!(seg.as_str(), "key" | "keys")
+                && (segments.len() == 1
+                    || i > 0 && KEY_QUALIFIERS.contains(&segments[i - 1].as_str())))
+    })
+}
+
+/// Whether `keyword` appears in `context` as whole identifier segments:
+/// `aws` in `AWS_SECRET` or `awsKey`, but not in `laws`.
+pub fn has_segments(context: &str, keyword: &str) -> bool {
+    let wanted = identifier_segments(keyword);
+    !wanted.is_empty()
+        && identifier_segments(context)
+            .windows(wanted.len())
+            .any(|w| w == wanted.as_slice())
+}
+
+/// Lowercased segments of an identifier, split at separators and camelCase
+/// humps: `X-ApiKey` and `x_api_key` both give `x`, `api`, `key`.
+fn identifier_segments(name: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    for part in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut current = String::new();
+        for (i, &c) in chars.iter().enumerate() {
+            let hump = c.is_ascii_uppercase()
+                && i > 0
+                && (chars[i - 1].is_ascii_lowercase()
+                    || chars[i - 1].is_ascii_uppercase()
+                        && chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase()));
+            if hump && !current.is_empty() {
+                segments.push(std::mem::take(&mut current));
+            }
+            current.push(c.to_ascii_lowercase());
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+    }
+    segments
+}
+
+/// Byte ranges of `text` whose high-entropy content is expected and harmless.
+pub fn benign_regions(text: &str) -> Vec<(usize, usize)> {
+    BENIGN_REGION_RE
+        .find_iter(text)
+        .map(|m| (m.start(), m.end()))
+        .collect()
+}
+
+fn char_class_mix(s: &str) -> f64 {
+    let lower = s.chars().any(|c| c.is_ascii_lowercase());
+    let upper = s.chars().any(|c| c.is_ascii_uppercase());
+    let digit = s.chars().any(|c| c.is_ascii_digit());
+    let other = s.chars().any(|c| !c.is_ascii_alphanumeric());
+    [lower, upper, digit, other].iter().filter(|&&b| b).count() as f64 / 4.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_token_scores_above_threshold() {
+        let s = score_token("AbCDeF0123456789AbCDeF0123456789", "", false);
+        assert!(s >= DEFAULT_TOKEN_THRESHOLD, "{}", s);
+    }
+
+    #[test]
+    fn git_sha_is_benign_without_keyword() {
+        let sha = "e83c5163316f89bfbd7d9ab23ca2e25604af290a";
+        assert!(score_token(sha, "commit ", false) < DEFAULT_TOKEN_THRESHOLD);
+        assert!(score_token(sha, "secret: ", false) >= DEFAULT_TOKEN_THRESHOLD);
+    }
+
+    #[test]
+    fn short_password_scores_with_keyword() {
+        assert!(score_token("Hunter2!x", "password=", false) >= DEFAULT_TOKEN_THRESHOLD);
+        assert!(score_token("Hunter2!x", "", false) < DEFAULT_TOKEN_THRESHOLD);
+    }
+
+    #[test]
+    fn keywords_are_whole_identifier_segments() {
+        for context in [
+            "password=",
+            "db_password = ",
+            "API_KEY=",
+            "X-Api-Key: ",
+            "accessToken: ",
+            "apikey=",
+            "Authorization: Bearer ",
+            "key=",
+        ] {
+            assert!(has_keyword(context), "{}", context);
+        }
+        for context in [
+            "monkey=",
+            "author: ",
+            "keyboard_layout=",
+            "sortKey=",
+            "primary_key=",
+        ] {
+            assert!(!has_keyword(context), "{}", context);
+        }
+    }
+
+    #[test]
+    fn classifies_alphabets() {
+        assert_eq!(Alphabet::of("deadbeef0123"), Alphabet::Hex);
+        assert_eq!(Alphabet::of("JBSWY3DPEHPK3PXP"), Alphabet::Base32);
+        assert_eq!(Alphabet::of("AbCdXyZ0123456789"), Alphabet::Alphanumeric);
+        assert_eq!(Alphabet::of("AbC_DeF-0123"), Alphabet::Base64Url);
+        assert_eq!(Alphabet::of("AbC+DeF/0123=="), Alphabet::Base64);
+        assert_eq!(Alphabet::of("Hunter2!x"), Alphabet::Printable);
+    }
+
+    #[test]
+    fn hex_judged_on_its_own_scale() {
+        let t = EntropyThresholds::default();
+        // ~3.9 bits: near the hex maximum, so it passes the hex floor.
+        assert!(t.passes("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"));
+        let strict = EntropyThresholds {
+            hex: 3.95,
+            ..EntropyThresholds::default()
+        };
+        assert!(!strict.passes("9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"));
+    }
+
+    #[test]
+    fn finds_integrity_hash_region() {
+        let text = r#""integrity": "sha512-AbCDeF0123456789+/AbCDeF0123456789==""#;
+        assert_eq!(benign_regions(text).len(), 1);
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+/// How serious a finding is, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Lowest first; `Severity as usize` indexes this and `Summary::severities`.
+    pub const ALL: [Severity; 4] = [
+        Severity::Low,
+        Severity::Medium,
+        Severity::High,
+        Severity::Critical,
+    ];
+    pub const NAMES: [&'static str; 4] = ["low", "medium", "high", "critical"];
+
+    pub fn from_name(name: &str) -> Option<Severity> {
+        Severity::NAMES
+            .iter()
+            .position(|n| *n == name)
+            .map(|i| Severity::ALL[i])
+    }
+
+    pub fn name(self) -> &'static str {
+        Severity::NAMES[self as usize]
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Built-in severity of each kind: credentials rank above identifiers.
+pub fn default_severity(kind: &str) -> Severity {
+    match kind {
+        "ip" | "uuid" => Severity::Low,
+        "jwt" | "token" => Severity::High,
+        _ => Severity::Medium,
+    }
+}
+
+/// Per-kind severities, falling back to [`default_severity`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Severities {
+    levels: BTreeMap<String, Severity>,
+}
+
+impl Severities {
+    pub fn set(&mut self, kind: &str, severity: Severity) {
+        self.levels.insert(kind.to_string(), severity);
+    }
+
+    pub fn get(&self, kind: &str) -> Severity {
+        self.levels
+            .get(kind)
+            .copied()
+            .unwrap_or_else(|| default_severity(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_and_overrides_levels() {
+        assert!(Severity::Critical > Severity::High);
+        assert_eq!(Severity::from_name("medium"), Some(Severity::Medium));
+        assert_eq!(Severity::from_name("severe"), None);
+
+        let mut s = Severities::default();
+        assert_eq!(s.get("jwt"), Severity::High);
+        s.set("email", Severity::Critical);
+        assert_eq!(s.get("email"), Severity::Critical);
+        assert_eq!(s.get("custom"), Severity::Medium);
+    }
+}
@@ -0,0 +1,174 @@
+//! Expands file and directory arguments into the text files to scan.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use ignore::WalkBuilder;
+
+/// Per-directory ignore file, read like `.gitignore`.
+pub const IGNORE_FILE: &str = ".scrubbyignore";
+
+/// Files larger than this are skipped unless the limit is raised.
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Why a file was not scanned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Skip {
+    /// NUL bytes or invalid UTF-8.
+    Binary,
+    /// Its size in bytes, over the limit.
+    TooLarge(u64),
+}
+
+impl std::fmt::Display for Skip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Skip::Binary => write!(f, "binary file"),
+            Skip::TooLarge(size) => write!(f, "too large ({} bytes)", size),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct WalkError {
+    pub message: String,
+}
+
+impl std::fmt::Display for WalkError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Files named in `paths`, and every file under the directories in it that
+/// `.gitignore`, `.git/info/exclude` or `.scrubbyignore` do not exclude.
+/// Hidden files such as `.env` are included; `.git` directories are not.
+/// Named files are always included, even if an ignore file matches them.
+pub fn walk(paths: &[PathBuf]) -> (Vec<PathBuf>, Vec<WalkError>) {
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            match fs::metadata(path) {
+                Ok(_) => files.push(path.clone()),
+                Err(e) => errors.push(WalkError {
+                    message: format!("{}: {}", path.display(), e),
+                }),
+            }
+            continue;
+        }
+        let walker = WalkBuilder::new(path)
+            .hidden(false)
+            .require_git(false)
+            .add_custom_ignore_filename(IGNORE_FILE)
+            .filter_entry(|e| e.file_name() != ".git")
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+        for entry in walker {
+            match entry {
+                Ok(e) if e.file_type().is_some_and(|t| t.is_file()) => files.push(e.into_path()),
+                Ok(_) => {}
+                Err(e) => errors.push(WalkError {
+                    message: e.to_string(),
+                }),
+            }
+        }
+    }
+    (files, errors)
+}
+
+/// Reads `path` as text. `Ok(Err(skip))` for files that are binary or bigger
+/// than `max_size` bytes.
+pub fn read_text(path: &Path, max_size: u64) -> std::io::Result<Result<String, Skip>> {
+    let size = fs::metadata(path)?.len();
+    if size > max_size {
+        return Ok(Err(Skip::TooLarge(size)));
+    }
+    let mut bytes = Vec::with_capacity(size as usize);
+    fs::File::open(path)?
+        .take(max_size + 1)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > max_size {
+        return Ok(Err(Skip::TooLarge(bytes.len() as u64)));
+    }
+    if bytes[..bytes.len().min(8000)].contains(&0) {
+        return Ok(Err(Skip::Binary));
+    }
+    Ok(String::from_utf8(bytes).map_err(|_| Skip::Binary))
+}
+
+/// Parses a size such as `500000`, `64K` or `10M` (powers of 1024).
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (digits, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => value.split_at(i),
+        None => (value, ""),
+    };
+    let factor = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    digits.parse::<u64>().ok()?.checked_mul(factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_directories_respecting_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (name, content) in [
+            (".gitignore", "target/\n*.log\n"),
+            (".scrubbyignore", "fixtures/\n"),
+            (".env", "TOKEN=x\n"),
+            ("src/main.rs", "fn main() {}\n"),
+            ("target/out.txt", "built\n"),
+            ("debug.log", "log\n"),
+            ("fixtures/keys.txt", "fake\n"),
+            (".git/config", "[core]\n"),
+        ] {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        let (files, errors) = walk(&[root.to_path_buf(), root.join("debug.log")]);
+        assert!(errors.is_empty());
+        let names: Vec<String> = files
+            .iter()
+            .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ".env",
+                ".gitignore",
+                ".scrubbyignore",
+                "src/main.rs",
+                "debug.log"
+            ]
+        );
+
+        let (_, errors) = walk(&[root.join("missing")]);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn skips_binary_and_large_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("f");
+        fs::write(&path, b"PNG\0\x01").unwrap();
+        assert_eq!(read_text(&path, 100).unwrap(), Err(Skip::Binary));
+        fs::write(&path, "hello").unwrap();
+        assert_eq!(read_text(&path, 4).unwrap(), Err(Skip::TooLarge(5)));
+        assert_eq!(read_text(&path, 5).unwrap(), Ok("hello".to_string()));
+        assert_eq!(parse_size("64K"), Some(65536));
+        assert_eq!(parse_size("2mb"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_size("lots"), None);
+    }
+}